@@ -3,7 +3,8 @@
 use guicss::iced;
 use guicss::integration::iced::{CssEvent, IdAndClasses, SharedRules, CSS};
 use iced::subscription::Subscription;
-use iced::{Application, Command, Element, Settings};
+use iced::widget::Space;
+use iced::{Application, Command, Element, Length, Settings};
 
 fn main() -> iced::Result {
   App::run(Settings::default())
@@ -56,6 +57,9 @@ impl Application for App {
 
   fn view(&self) -> Element<'_, Self::Message, iced::Renderer<Self::Theme>> {
     let def = IdAndClasses::parse("#test.foo.bar");
+    if !self.css.is_displayed("button", def) {
+      return Space::new(Length::Units(0), Length::Units(0)).into();
+    }
     self.css.button("Foobar", def).on_press(Message::ButtonClicked).into()
   }
 
@@ -0,0 +1,400 @@
+use lightningcss::properties::PropertyId;
+use log::warn;
+
+use crate::properties::{
+  Animation, AnimationDirection, Color, ComputedProperties, FillMode, IterationCount, Length, PropertyTransition, Shadow, TimingFunction, TransitionProperty,
+  Visibility,
+};
+
+/// Values transitions can interpolate. Values that can't be interpolated,
+/// like `auto` and a length, flip half-way.
+pub(crate) trait Interpolate: Clone {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self;
+}
+
+fn discrete<T: Clone>(from: &T, to: &T, progress: f32) -> T {
+  if progress < 0.5 {
+    from.clone()
+  } else {
+    to.clone()
+  }
+}
+
+impl Interpolate for f32 {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self {
+    self + (to - self) * progress
+  }
+}
+
+/// Interpolated with premultiplied alpha, so that fading from transparent
+/// doesn't go through black.
+impl Interpolate for Color {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self {
+    let alpha = self.a.interpolate(&to.a, progress);
+    if alpha <= 0.0 {
+      return Color::transparent();
+    }
+    let channel = |c1: f32, c2: f32| (c1 * self.a).interpolate(&(c2 * to.a), progress) / alpha;
+    Color {
+      r: channel(self.r, to.r),
+      g: channel(self.g, to.g),
+      b: channel(self.b, to.b),
+      a: alpha,
+    }
+  }
+}
+
+impl Interpolate for Length {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self {
+    // Lengths of different units are interpolated as a sum, like
+    // `calc(50% + 10px)`.
+    match (self.to_sum(), to.to_sum()) {
+      (Some(a), Some(b)) => (a * (1.0 - progress) + b * progress).into_length(),
+      _ => discrete(self, to, progress),
+    }
+  }
+}
+
+impl<T: Interpolate> Interpolate for Option<T> {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self {
+    match (self, to) {
+      (Some(a), Some(b)) => Some(a.interpolate(b, progress)),
+      _ => discrete(self, to, progress),
+    }
+  }
+}
+
+impl<T: Interpolate> Interpolate for (T, T) {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self {
+    (self.0.interpolate(&to.0, progress), self.1.interpolate(&to.1, progress))
+  }
+}
+
+/// Visible during the whole transition if either end is visible.
+impl Interpolate for Visibility {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self {
+    match (self, to) {
+      (Visibility::Visible, _) if progress < 1.0 => Visibility::Visible,
+      (_, Visibility::Visible) if progress > 0.0 => Visibility::Visible,
+      _ => discrete(self, to, progress),
+    }
+  }
+}
+
+/// Lists of shadows are padded with transparent shadows to the same length.
+impl Interpolate for Vec<Shadow> {
+  fn interpolate(&self, to: &Self, progress: f32) -> Self {
+    let none = |s: &Shadow| {
+      Shadow {
+        x: 0.0,
+        y: 0.0,
+        blur: 0.0,
+        spread: 0.0,
+        color: Some(Color::transparent()),
+        inset: s.inset,
+      }
+    };
+    let len = self.len().max(to.len());
+    let mut shadows = Vec::with_capacity(len);
+    for i in 0..len {
+      let (a, b) = match (self.get(i), to.get(i)) {
+        (Some(a), Some(b)) => (*a, *b),
+        (Some(a), None) => (*a, none(a)),
+        (None, Some(b)) => (none(b), *b),
+        (None, None) => unreachable!(),
+      };
+      if a.inset != b.inset {
+        return discrete(self, to, progress);
+      }
+      shadows.push(Shadow {
+        x: a.x.interpolate(&b.x, progress),
+        y: a.y.interpolate(&b.y, progress),
+        blur: a.blur.interpolate(&b.blur, progress).max(0.0),
+        spread: a.spread.interpolate(&b.spread, progress),
+        color: a.color.interpolate(&b.color, progress),
+        inset: a.inset,
+      });
+    }
+    shadows
+  }
+}
+
+/// Interpolates the properties of an element between two styles, for
+/// instance before and after it is hovered, or before and after a theme
+/// change.
+///
+/// ```
+/// # use guicss::animation::Transition;
+/// # use guicss::element::{Element, PseudoClass};
+/// # use guicss::parser::parse_string_sync;
+/// let rules = parse_string_sync("button { transition: color 1s } button:hover { color: red }", None).unwrap();
+/// let from = rules.compute(&Element::named("button"));
+/// let to = rules.compute(&Element::named("button").pseudo_class(PseudoClass::Hover));
+/// let transition = Transition::new(from, to);
+/// let half_way = transition.at(0.5);
+/// assert!(!transition.is_finished(0.5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Transition {
+  from: ComputedProperties,
+  to: ComputedProperties,
+  transitions: Vec<PropertyTransition>,
+}
+
+impl Transition {
+  /// As in CSS, the transitions are the ones of the new style, `to`.
+  pub fn new(from: ComputedProperties, to: ComputedProperties) -> Transition {
+    let transitions = to.transitions();
+    Transition { from, to, transitions }
+  }
+
+  /// Time, in seconds, after which all the properties have their final
+  /// value.
+  pub fn duration(&self) -> f32 {
+    self.transitions.iter().map(|t| t.delay + t.duration).fold(0.0, f32::max)
+  }
+
+  pub fn is_finished(&self, elapsed: f32) -> bool {
+    elapsed >= self.duration()
+  }
+
+  /// Properties `elapsed` seconds after the change of style. Properties
+  /// without a transition have their final value.
+  pub fn at(&self, elapsed: f32) -> ComputedProperties {
+    let mut p = self.to.clone();
+    // The last transition of a property wins.
+    interpolate(&mut p, |name| {
+      let t = self.transitions.iter().rev().find(|t| t.property.covers(name))?;
+      let progress = t.timing_function.progress((elapsed - t.delay) / t.duration);
+      Some((&self.from, &self.to, progress))
+    });
+    p
+  }
+}
+
+macro_rules! interpolated {
+  ($($name:literal => $($field:ident).+;)*) => {
+    /// Longhand properties that are interpolated. The others change
+    /// discretely.
+    pub(crate) const INTERPOLATED: &[&str] = &[$($name),*];
+
+    /// Interpolate the properties of `p` for which `segment` returns the
+    /// values to interpolate between, and the progress between them.
+    fn interpolate<'a>(p: &mut ComputedProperties, segment: impl Fn(&str) -> Option<(&'a ComputedProperties, &'a ComputedProperties, f32)>) {
+      $(if let Some((from, to, progress)) = segment($name) {
+        p.$($field).+ = from.$($field).+.interpolate(&to.$($field).+, progress);
+      })*
+    }
+  };
+}
+
+interpolated! {
+  "color" => color;
+  "background-color" => background_color;
+  "opacity" => opacity;
+  "visibility" => visibility;
+
+  "border-top-width" => border.top.width;
+  "border-right-width" => border.right.width;
+  "border-bottom-width" => border.bottom.width;
+  "border-left-width" => border.left.width;
+  "border-top-color" => border.top.color;
+  "border-right-color" => border.right.color;
+  "border-bottom-color" => border.bottom.color;
+  "border-left-color" => border.left.color;
+  "border-top-left-radius" => border_radius.nw;
+  "border-top-right-radius" => border_radius.ne;
+  "border-bottom-left-radius" => border_radius.sw;
+  "border-bottom-right-radius" => border_radius.se;
+  "outline-width" => outline.width;
+  "outline-color" => outline.color;
+  "outline-offset" => outline.offset;
+
+  "margin-top" => margin.top;
+  "margin-right" => margin.right;
+  "margin-bottom" => margin.bottom;
+  "margin-left" => margin.left;
+  "padding-top" => padding.top;
+  "padding-right" => padding.right;
+  "padding-bottom" => padding.bottom;
+  "padding-left" => padding.left;
+  "width" => width;
+  "height" => height;
+  "min-width" => min_width;
+  "min-height" => min_height;
+  "max-width" => max_width;
+  "max-height" => max_height;
+  "flex-grow" => flex_grow;
+  "flex-shrink" => flex_shrink;
+  "flex-basis" => flex_basis;
+  "row-gap" => row_gap;
+  "column-gap" => column_gap;
+
+  "font-size" => font_size;
+  "letter-spacing" => letter_spacing;
+  "box-shadow" => box_shadow;
+  "text-shadow" => text_shadow;
+}
+
+/// A keyframe of a `@keyframes` rule, computed for an element.
+#[derive(Clone, Debug)]
+pub(crate) struct Keyframe {
+  /// Between 0 and 1.
+  pub(crate) offset: f32,
+  pub(crate) properties: ComputedProperties,
+  /// Properties declared by the keyframe.
+  pub(crate) declared: Vec<TransitionProperty>,
+  /// Longhands declared by the keyframe that are not interpolated, and
+  /// change when the keyframe is reached.
+  pub(crate) discrete: Vec<PropertyId<'static>>,
+  /// `animation-timing-function` declared by the keyframe.
+  pub(crate) timing_function: Option<TimingFunction>,
+}
+
+impl Animation {
+  /// Time, in seconds, after which the animation has ended. Infinite if
+  /// the animation repeats forever.
+  pub fn end(&self) -> f32 {
+    let iterations = match self.iteration_count {
+      IterationCount::Count(n) => n,
+      IterationCount::Infinite => f32::INFINITY,
+    };
+    let active = if self.duration > 0.0 { self.duration * iterations } else { 0.0 };
+    self.delay + active
+  }
+
+  /// Progress through the keyframes `time` seconds after the animation was
+  /// created, between 0 and 1. `None` when the animation doesn't apply,
+  /// before it starts or after it ends, depending on its fill mode.
+  /// See <https://drafts.csswg.org/web-animations/#calculating-progress>
+  pub fn progress(&self, time: f32) -> Option<f32> {
+    let iterations = match self.iteration_count {
+      IterationCount::Count(n) => n,
+      IterationCount::Infinite => f32::INFINITY,
+    };
+    let local = time - self.delay;
+    let (iteration, progress) = if local < 0.0 {
+      if !matches!(self.fill_mode, FillMode::Backwards | FillMode::Both) {
+        return None;
+      }
+      (0.0, 0.0)
+    } else if time >= self.end() {
+      if !matches!(self.fill_mode, FillMode::Forwards | FillMode::Both) {
+        return None;
+      }
+      // Ends at the end of the last iteration, unless it stops part-way.
+      match iterations.fract() {
+        f if f == 0.0 && iterations > 0.0 => (iterations - 1.0, 1.0),
+        f => (iterations.floor(), f),
+      }
+    } else {
+      let overall = local / self.duration;
+      (overall.floor(), overall.fract())
+    };
+    let odd = iteration % 2.0 == 1.0;
+    let reverse = match self.direction {
+      AnimationDirection::Normal => false,
+      AnimationDirection::Reverse => true,
+      AnimationDirection::Alternate => odd,
+      AnimationDirection::AlternateReverse => !odd,
+    };
+    Some(if reverse { 1.0 - progress } else { progress })
+  }
+}
+
+/// Animations of an element, all started at the same time. Time only
+/// goes through the timeline by the timestamps passed to
+/// [`Timeline::at`].
+///
+/// ```
+/// # use guicss::element::Element;
+/// # use guicss::parser::parse_string_sync;
+/// let source = "@keyframes pulse { 50% { opacity: 0.5 } } button { animation: pulse 1s infinite }";
+/// let rules = parse_string_sync(source, None).unwrap();
+/// let button = rules.compute(&Element::named("button"));
+/// let timeline = rules.timeline(&button, None);
+/// assert_eq!(timeline.at(0.5).opacity, 0.5);
+/// assert!(!timeline.is_finished(10.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Timeline {
+  properties: ComputedProperties,
+  animations: Vec<(Animation, Vec<Keyframe>)>,
+}
+
+impl Timeline {
+  pub(crate) fn new(properties: ComputedProperties, animations: Vec<(Animation, Vec<Keyframe>)>) -> Timeline {
+    Timeline { properties, animations }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.animations.is_empty()
+  }
+
+  /// Whether all the animations have ended: properties won't change
+  /// anymore.
+  pub fn is_finished(&self, time: f32) -> bool {
+    self.animations.iter().all(|(a, _)| time >= a.end())
+  }
+
+  /// Properties `time` seconds after the animations were created. Later
+  /// animations override earlier ones.
+  pub fn at(&self, time: f32) -> ComputedProperties {
+    let mut p = self.properties.clone();
+    for (animation, keyframes) in &self.animations {
+      let progress = match animation.progress(time) {
+        Some(progress) => progress,
+        None => continue,
+      };
+      let base = &self.properties;
+      interpolate(&mut p, |name| {
+        // Keyframes that declare the property, with the properties of the
+        // element as implicit first and last keyframes.
+        let mut frames: Vec<_> = keyframes
+          .iter()
+          .filter(|k| k.declared.iter().any(|d| d.covers(name)))
+          .map(|k| (k.offset, &k.properties, k.timing_function))
+          .collect();
+        let (first, last) = (frames.first()?.0, frames.last()?.0);
+        if first > 0.0 {
+          frames.insert(0, (0.0, base, None));
+        }
+        if last < 1.0 {
+          frames.push((1.0, base, None));
+        }
+        let i = frames.windows(2).position(|w| progress <= w[1].0).unwrap_or(frames.len() - 2);
+        let ((start, from, timing_function), (end, to, _)) = (frames[i], frames[i + 1]);
+        let local = if end > start { (progress - start) / (end - start) } else { 1.0 };
+        let timing_function = timing_function.unwrap_or(animation.timing_function);
+        Some((from, to, timing_function.progress(local)))
+      });
+      let mut discrete: Vec<&PropertyId<'_>> = vec![];
+      for id in keyframes.iter().flat_map(|k| &k.discrete) {
+        if !discrete.contains(&id) {
+          discrete.push(id);
+        }
+      }
+      for id in discrete {
+        // Value of the last keyframe reached, with the properties of the
+        // element as implicit first and last keyframes.
+        let mut frames: Vec<_> = keyframes
+          .iter()
+          .filter(|k| k.discrete.contains(id))
+          .map(|k| (k.offset, &k.properties))
+          .collect();
+        if frames.last().map_or(false, |(offset, _)| *offset < 1.0) {
+          frames.push((1.0, base));
+        }
+        let from = frames
+          .iter()
+          .rev()
+          .find(|(offset, _)| *offset <= progress)
+          .map_or(base, |(_, properties)| properties);
+        if let Err(e) = p.copy_property(id, from) {
+          warn!("{e}");
+        }
+      }
+    }
+    p
+  }
+}
@@ -1,33 +1,63 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use lightningcss::declaration::DeclarationBlock;
 use lightningcss::media_query::{MediaFeature, MediaFeatureValue, Operator, Qualifier};
 use lightningcss::parcel_selectors::context::QuirksMode;
 use lightningcss::parcel_selectors::matching::{matches_selector, MatchingContext, MatchingMode};
-use lightningcss::parcel_selectors::parser::Selector;
-use lightningcss::printer::Printer;
-use lightningcss::properties::custom::{CustomProperty, TokenOrValue};
-use lightningcss::properties::Property;
+use lightningcss::parcel_selectors::parser::{Combinator, Component, Selector};
+use lightningcss::properties::custom::{CustomProperty, TokenList};
+use lightningcss::properties::{Property, PropertyId};
+use lightningcss::rules::keyframes::{KeyframeSelector, KeyframesName};
 use lightningcss::rules::media::MediaRule;
 use lightningcss::rules::style::StyleRule;
 use lightningcss::rules::CssRule;
-use lightningcss::selector::Selectors;
-use lightningcss::stylesheet::{ParserOptions, PrinterOptions, StyleSheet};
+use lightningcss::selector::{PseudoClass, PseudoElement, Selectors};
+use lightningcss::stylesheet::StyleSheet;
+use lightningcss::traits::IntoOwned;
 use log::warn;
 
-use crate::element::Element;
-use crate::properties::ComputedProperties;
-use crate::themes::SystemTheme;
+use crate::animation::{Keyframe, Timeline, INTERPOLATED};
+use crate::element::{is_registered_pseudo_class, is_registered_pseudo_element, Element, Node};
+use crate::properties::{longhands, ComputedProperties, Context, FontFace, TimingFunction, TransitionProperty, WideKeyword};
+use crate::themes::{SystemColors, SystemTheme};
+use crate::variables::{has_references, parse_tokens, system_variables, Registration, Resolver};
+
+/// Arguments of a `:has()` pseudo-class.
+type RelativeSelectors<'i> = Box<[Selector<'i, Selectors>]>;
 
 pub(crate) struct PreComputedRules<'i> {
-  pub(crate) rules: Vec<(Selector<'i, Selectors>, DeclarationBlock<'i>)>,
+  /// Rules sorted by specificity, with the arguments of the `:has()`
+  /// pseudo-classes of their subject, which are matched separately.
+  pub(crate) rules: Vec<(Selector<'i, Selectors>, Vec<RelativeSelectors<'i>>, DeclarationBlock<'i>)>,
+  /// Custom properties registered with `@property`.
+  pub(crate) registered: HashMap<String, Registration>,
+  /// Custom properties set by the system. `var()` falls back to them, but
+  /// they are not part of the computed `variables` of elements.
+  pub(crate) variables: HashMap<String, TokenList<'static>>,
+  pub(crate) font_faces: Vec<FontFace>,
+  /// Keyframes of `@keyframes` rules, sorted by offset.
+  pub(crate) keyframes: HashMap<String, Vec<(f32, DeclarationBlock<'i>)>>,
+  /// Directory `url()`s are relative to.
+  pub(crate) base: Option<PathBuf>,
+  /// System colors of the theme the stylesheet was computed for.
+  pub(crate) system_colors: SystemColors,
+  /// Problems found in the stylesheet that the application should know
+  /// about, like unregistered pseudo-classes.
+  pub(crate) warnings: Vec<String>,
 }
 
 // FIXME: CSS errors are only reported with warn!. We should send them back as
 // events #4
 
-pub(crate) fn pre_compute<'i>(stylesheet: StyleSheet<'i, '_>) -> PreComputedRules<'i> {
+/// `base` is the directory `url()`s are relative to.
+pub(crate) fn pre_compute<'i>(stylesheet: StyleSheet<'i, '_>, base: Option<&Path>) -> PreComputedRules<'i> {
   let theme = crate::themes::get_system_theme();
+  let system_colors = SystemColors::new(theme);
+  let mut registered = HashMap::new();
+  let mut font_faces = vec![];
+  let mut keyframes = HashMap::new();
+  let mut warnings = vec![];
   // Iterator over all the rules, including rules under matching MediaQueries
   let rules_iter = stylesheet.rules.0.into_iter();
   let mut all_rules: Vec<_> = rules_iter
@@ -35,6 +65,33 @@ pub(crate) fn pre_compute<'i>(stylesheet: StyleSheet<'i, '_>) -> PreComputedRule
       match rule {
         CssRule::Style(style) => [style].to_vec(),
         CssRule::Media(m) => compute_media_queries(m, theme),
+        CssRule::Property(p) => {
+          // Last registration wins.
+          registered.insert(p.name.0.to_string(), Registration::new(&p));
+          vec![]
+        },
+        CssRule::FontFace(f) => {
+          font_faces.push(FontFace::new(&f, base));
+          vec![]
+        },
+        CssRule::Keyframes(k) => {
+          let name = match &k.name {
+            KeyframesName::Ident(ident) => ident.0.to_string(),
+            KeyframesName::Custom(name) => name.to_string(),
+          };
+          let mut frames: Vec<_> = k
+            .keyframes
+            .into_iter()
+            .flat_map(|keyframe| {
+              let offsets: Vec<_> = keyframe.selectors.iter().filter_map(keyframe_offset).collect();
+              offsets.into_iter().map(move |offset| (offset, keyframe.declarations.clone()))
+            })
+            .collect();
+          frames.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+          // Last definition wins.
+          keyframes.insert(name, frames);
+          vec![]
+        },
         unknown => {
           warn!("Unsupported CSS Rule: {unknown:?}");
           vec![]
@@ -42,23 +99,41 @@ pub(crate) fn pre_compute<'i>(stylesheet: StyleSheet<'i, '_>) -> PreComputedRule
       }
     })
     .flat_map(|style| style.selectors.0.into_iter().map(move |s| (s, style.declarations.clone())))
+    .filter_map(|(selector, declarations)| {
+      for warning in unknown_pseudos(&selector) {
+        warn!("{warning}");
+        warnings.push(warning);
+      }
+      let (selector, has) = extract_has(selector)?;
+      Some((selector, has, declarations))
+    })
     .collect();
 
   // Sort all rules by specificity.
-  all_rules.sort_by(|(s1, _), (s2, _)| s1.specificity().cmp(&s2.specificity()));
-  PreComputedRules { rules: all_rules }
+  all_rules.sort_by(|(s1, ..), (s2, ..)| s1.specificity().cmp(&s2.specificity()));
+  PreComputedRules {
+    rules: all_rules,
+    registered,
+    variables: system_variables(&system_colors),
+    font_faces,
+    keyframes,
+    base: base.map(Path::to_path_buf),
+    system_colors,
+    warnings,
+  }
 }
 
 impl<'i> PreComputedRules<'i> {
-  pub(crate) fn compute(&self, element: &Element<'_>) -> ComputedProperties {
+  pub(crate) fn compute(&self, element: &Element<'_>, parent: Option<&ComputedProperties>, context: &Context) -> ComputedProperties {
     // Iterator over all the rules, including rules under matching MediaQueries
     // Only keep Vec<declarations> of matching rules
     let mut ctx = MatchingContext::new(MatchingMode::Normal, None, None, QuirksMode::NoQuirks);
+    let node = Node::new(element);
     let matching: (Vec<_>, Vec<_>) = self
       .rules
       .iter()
-      .filter_map(|(s, decs)| {
-        if matches_selector(s, 0, None, &element, &mut ctx, &mut |_, _| {}) {
+      .filter_map(|(s, has, decs)| {
+        if matches_selector(s, 0, None, &node, &mut ctx, &mut |_, _| {}) && has.iter().all(|selectors| element.has(selectors)) {
           Some((&decs.declarations, &decs.important_declarations))
         } else {
           None
@@ -66,7 +141,8 @@ impl<'i> PreComputedRules<'i> {
       })
       .unzip();
 
-    // Flatten the declarations and sort them with important declarations at the end.
+    // Flatten the declarations and sort them with important declarations at the
+    // end.
     let (normal_matching, important_matching) = matching;
     let normal_matching = normal_matching.into_iter().flatten();
     let important_matching = important_matching.into_iter().flatten();
@@ -75,54 +151,292 @@ impl<'i> PreComputedRules<'i> {
     // Declarations are now sorted from least specific to more specific, with
     // importants at the end.
 
-    let mut variables = HashMap::new();
+    // Inherited properties and variables come from the parent.
+    let mut computed = parent.map_or_else(ComputedProperties::default, ComputedProperties::inherit);
+    let initial = ComputedProperties::default();
+    let inherited = parent.map_or(&initial.variables, |p| &p.variables);
+    let parent = parent.unwrap_or(&initial);
 
-    // Remove `--x: y` declarations, and save them in hashmap.
+    // Remove `--x: y` declarations, and compute them.
+    let mut resolver = Resolver::new(inherited, &self.registered, &self.variables);
     let without_var: Vec<_> = matching
       .filter(|prop| {
-        if let Property::Custom(CustomProperty { name, value: tokens }) = prop {
+        if let Property::Custom(CustomProperty { name, value }) = prop {
           if name.starts_with("--") {
-            let mut source = String::new();
-            let mut printer = Printer::new(&mut source, PrinterOptions::default());
-            // FIXME: Do not serialise and parse variables values #1
-            tokens.to_css(&mut printer, false).unwrap();
-            variables.insert(name.clone(), source.clone());
+            resolver.declare(name.as_ref(), value);
             return false;
           }
         }
         true
       })
       .collect();
+    let (variables, registered_variables) = resolver.computed();
+    computed.variables = variables;
+    computed.registered_variables = registered_variables;
+
+    self.apply_all(&mut computed, without_var, &mut resolver, parent, context);
+    computed.content.resolve_attributes(|name| element.get_attribute(name));
+    computed
+  }
+
+  /// Apply `declarations`, substituting their `var()` references.
+  fn apply_all(
+    &self,
+    computed: &mut ComputedProperties,
+    declarations: Vec<&Property<'i>>,
+    resolver: &mut Resolver<'_, 'i>,
+    parent: &ComputedProperties,
+    context: &Context,
+  ) {
+    // `em` lengths depend on the font size, and `currentColor` on the color,
+    // whichever declaration comes first. Their winning values are cascaded
+    // first, then all the declarations are applied in cascade order.
+    let mut early = computed.clone();
+    for prop in declarations.iter().filter(|prop| sets_font_size_or_color(prop)) {
+      self.apply_one(&mut early, prop, resolver, parent, context);
+    }
+    // The font size and the color only come from the first pass.
+    let rest = declarations
+      .into_iter()
+      .filter(|prop| !matches!(prop.property_id(), PropertyId::FontSize | PropertyId::Color));
+    for prop in rest {
+      computed.font_size = early.font_size;
+      computed.color = early.color;
+      self.apply_one(computed, prop, resolver, parent, context);
+    }
+    computed.font_size = early.font_size;
+    computed.color = early.color;
+  }
+
+  fn apply_one(&self, computed: &mut ComputedProperties, prop: &Property<'i>, resolver: &mut Resolver<'_, 'i>, parent: &ComputedProperties, context: &Context) {
+    let substituted = match prop {
+      Property::Unparsed(p) if has_references(&p.value) => resolver.substitute(&p.value).map(|tokens| parse_tokens(&p.property_id, &tokens)),
+      // Properties unknown to lightningcss, like `content`, keep their tokens.
+      Property::Custom(CustomProperty { name, value }) if !name.as_ref().starts_with("--") && has_references(value) => {
+        resolver
+          .substitute(value)
+          .map(|value| Ok(Property::Custom(CustomProperty { name: name.clone(), value }).into_owned()))
+      },
+      _ => return apply_declaration(computed, prop, context, parent, self.base.as_deref(), &self.system_colors),
+    };
+    match substituted {
+      Some(Ok(prop)) => apply_declaration(computed, &prop, context, parent, self.base.as_deref(), &self.system_colors),
+      invalid => {
+        // Invalid at computed-value time: behaves as `unset`.
+        match invalid {
+          Some(Err(e)) => warn!("{e}"),
+          _ => warn!("Could not resolve variables of property {}", prop.property_id().name()),
+        }
+        if let Err(e) = computed.apply_keyword(prop, WideKeyword::Unset, parent) {
+          warn!("{e}");
+        }
+      },
+    }
+  }
 
-    let mut computed = ComputedProperties::default();
-
-    // Apply all declarations to computed properties.
-    for prop in without_var {
-      if let Property::Unparsed(p) = prop {
-        let token = p.value.0.get(0);
-        if let Some(TokenOrValue::Var(v)) = token {
-          let name = v.name.ident.0.as_ref();
-          if let Some(source) = variables.get(name) {
-            let id = p.property_id.clone();
-            let parser_opts = ParserOptions::default();
-            if let Ok(prop) = Property::parse_string(id, source, parser_opts) {
-              if let Err(e) = computed.apply(&prop) {
-                warn!("{e}");
+  /// Keyframes of the `@keyframes` rule `name`, computed for an element
+  /// whose properties are `properties`.
+  fn keyframes(&self, name: &str, properties: &ComputedProperties, parent: &ComputedProperties, context: &Context) -> Option<Vec<Keyframe>> {
+    let rule = self.keyframes.get(name)?;
+    // `var()` references resolve to the custom properties of the element.
+    let mut resolver = Resolver::new(&properties.variables, &self.registered, &self.variables);
+    let mut keyframes: Vec<Keyframe> = vec![];
+    let initial = ComputedProperties::default();
+    for (offset, block) in rule {
+      // Keyframes with the same offset cascade.
+      if keyframes.last().map_or(true, |k| k.offset != *offset) {
+        keyframes.push(Keyframe {
+          offset: *offset,
+          properties: properties.clone(),
+          declared: vec![],
+          discrete: vec![],
+          timing_function: None,
+        });
+      }
+      let keyframe = keyframes.last_mut().unwrap();
+      // `!important` declarations are ignored in keyframes, custom
+      // properties can't be animated.
+      let declarations: Vec<_> = block
+        .declarations
+        .iter()
+        .filter(|prop| !matches!(prop, Property::Custom(CustomProperty { name, .. }) if name.starts_with("--")))
+        .collect();
+      for prop in &declarations {
+        match prop {
+          Property::AnimationTimingFunction(functions, _) => keyframe.timing_function = functions.first().map(TimingFunction::from),
+          prop => {
+            let id = prop.property_id();
+            // Properties that are not interpolated change at the keyframe.
+            for longhand in longhands(&id) {
+              if INTERPOLATED.contains(&longhand.name()) || keyframe.discrete.contains(&longhand) {
+                continue;
+              }
+              match ComputedProperties::default().copy_property(&longhand, &initial) {
+                Ok(()) => keyframe.discrete.push(longhand),
+                Err(_) => warn!("Property {} can't be animated", longhand.name()),
               }
-              continue;
-            } else {
-              warn!("Could not parse `{name}` variable content ({source}) for property {prop:?}");
             }
-          } else {
-            warn!("Could not resolve variable: {name}");
-          }
+            keyframe.declared.push(TransitionProperty::Property(id.name().to_owned()));
+          },
         }
       }
-      if let Err(e) = computed.apply(prop) {
-        warn!("{e}");
-      }
+      self.apply_all(&mut keyframe.properties, declarations, &mut resolver, parent, context);
     }
-    computed
+    Some(keyframes)
+  }
+
+  pub(crate) fn timeline(&self, properties: &ComputedProperties, parent: Option<&ComputedProperties>, context: &Context) -> Timeline {
+    let initial = ComputedProperties::default();
+    let parent = parent.unwrap_or(&initial);
+    let animations = properties
+      .animations()
+      .into_iter()
+      .filter_map(|animation| {
+        match self.keyframes(&animation.name, properties, parent, context) {
+          Some(keyframes) => Some((animation, keyframes)),
+          None => {
+            warn!("Unknown @keyframes {}", animation.name);
+            None
+          },
+        }
+      })
+      .collect();
+    Timeline::new(properties.clone(), animations)
+  }
+}
+
+/// Whether `prop` can set the font size or the color.
+fn sets_font_size_or_color(prop: &Property<'_>) -> bool {
+  match prop {
+    Property::Custom(CustomProperty { name, .. }) => name.as_ref() == "all",
+    prop => {
+      matches!(
+        prop.property_id(),
+        PropertyId::FontSize | PropertyId::Font | PropertyId::Color | PropertyId::All
+      )
+    },
+  }
+}
+
+fn apply_declaration(
+  computed: &mut ComputedProperties,
+  prop: &Property<'_>,
+  context: &Context,
+  parent: &ComputedProperties,
+  base: Option<&Path>,
+  colors: &SystemColors,
+) {
+  let result = match WideKeyword::of(prop) {
+    Some(keyword) => computed.apply_keyword(prop, keyword, parent),
+    None => computed.apply(prop, context, parent, base, colors),
+  };
+  if let Err(e) = result {
+    warn!("{e}");
+  }
+}
+
+/// Move the `:has()` pseudo-classes of the subject of `selector` out of it,
+/// as they are not supported by the selector matching. `None`, with a
+/// warning, if `:has()` is used elsewhere.
+fn extract_has(mut selector: Selector<'_, Selectors>) -> Option<(Selector<'_, Selectors>, Vec<RelativeSelectors<'_>>)> {
+  let mut has = vec![];
+  let mut in_subject = true;
+  for component in selector.iter_mut_raw_match_order() {
+    match component {
+      Component::Combinator(_) => in_subject = false,
+      // Matches any element, and keeps the specificity of the selector.
+      Component::Has(_) if in_subject => {
+        if let Component::Has(selectors) = std::mem::replace(component, Component::ExplicitUniversalType) {
+          if selectors.iter().any(contains_has) {
+            warn!("Unsupported nested :has()");
+            return None;
+          }
+          if selectors.iter().any(is_sibling_relative) {
+            warn!("Unsupported selector: :has() only supports descendants and children");
+            return None;
+          }
+          has.push(selectors);
+        }
+      },
+      _ => {},
+    }
+  }
+  if contains_has(&selector) {
+    warn!("Unsupported selector: :has() is only supported on the subject");
+    return None;
+  }
+  Some((selector, has))
+}
+
+fn contains_has(selector: &Selector<'_, Selectors>) -> bool {
+  any_component(selector, &mut |component| matches!(component, Component::Has(_)))
+}
+
+/// Whether `selector`, relative to the anchor of a `:has()`, starts with `+`
+/// or `~`. Elements have no siblings.
+fn is_sibling_relative(selector: &Selector<'_, Selectors>) -> bool {
+  let mut components = selector.iter_raw_match_order().rev();
+  matches!(
+    (components.next(), components.next()),
+    (
+      Some(Component::Scope),
+      Some(Component::Combinator(Combinator::NextSibling | Combinator::LaterSibling))
+    )
+  )
+}
+
+/// Pseudo-classes and pseudo-elements that are neither standard nor
+/// registered never match, nor do functional custom ones like `:state(x)`.
+/// Returns a warning for each of them.
+fn unknown_pseudos(selector: &Selector<'_, Selectors>) -> Vec<String> {
+  let mut warnings = vec![];
+  any_component(selector, &mut |component| {
+    match component {
+      Component::NonTSPseudoClass(PseudoClass::Custom(name)) if !is_registered_pseudo_class(name) => {
+        warnings.push(format!("Unknown pseudo-class :{name}, it must be registered with register_pseudo_classes()"));
+      },
+      Component::NonTSPseudoClass(PseudoClass::CustomFunction(name, _)) => {
+        warnings.push(format!("Unsupported pseudo-class :{name}(), custom pseudo-classes can't take arguments"));
+      },
+      Component::PseudoElement(PseudoElement::Custom(name)) if !is_registered_pseudo_element(name) => {
+        warnings.push(format!(
+          "Unknown pseudo-element ::{name}, it must be registered with register_pseudo_elements()"
+        ));
+      },
+      Component::PseudoElement(PseudoElement::CustomFunction(name, _)) => {
+        warnings.push(format!("Unsupported pseudo-element ::{name}(), custom pseudo-elements can't take arguments"));
+      },
+      _ => {},
+    }
+    false
+  });
+  warnings
+}
+
+/// Whether `f` is true for one of the components of `selector`, including
+/// the ones of its nested selectors.
+fn any_component<'i>(selector: &Selector<'i, Selectors>, f: &mut dyn FnMut(&Component<'i, Selectors>) -> bool) -> bool {
+  selector.iter_raw_match_order().any(|component| {
+    f(component) ||
+      match component {
+        Component::Is(list) | Component::Where(list) | Component::Negation(list) | Component::Any(_, list) | Component::Has(list) => {
+          list.iter().any(|s| any_component(s, f))
+        },
+        Component::NthOf(data) => data.selectors().iter().any(|s| any_component(s, f)),
+        _ => false,
+      }
+  })
+}
+
+fn keyframe_offset(selector: &KeyframeSelector) -> Option<f32> {
+  match selector {
+    KeyframeSelector::From => Some(0.0),
+    KeyframeSelector::To => Some(1.0),
+    KeyframeSelector::Percentage(p) => Some(p.0),
+    KeyframeSelector::TimelineRangePercentage(_) => {
+      warn!("Unsupported keyframe selector {selector:?}");
+      None
+    },
   }
 }
 
@@ -2,12 +2,63 @@ use std::collections::{HashMap, HashSet};
 
 use lightningcss::cssparser::ToCss;
 use lightningcss::parcel_selectors;
-use lightningcss::parcel_selectors::attr::{AttrSelectorOperation, AttrSelectorOperator, CaseSensitivity, NamespaceConstraint};
-use lightningcss::parcel_selectors::matching::{ElementSelectorFlags, MatchingContext};
-use lightningcss::parcel_selectors::OpaqueElement;
+use lightningcss::parcel_selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
+use lightningcss::parcel_selectors::context::QuirksMode;
+use lightningcss::parcel_selectors::matching::{matches_selector, ElementSelectorFlags, MatchingContext, MatchingMode};
+use lightningcss::parcel_selectors::parser::{Combinator, Component, Selector};
+use lightningcss::parcel_selectors::{Element as _, OpaqueElement};
 pub use lightningcss::selector::{PseudoClass, PseudoElement};
 use lightningcss::selector::{SelectorIdent, SelectorString, Selectors};
-use log::warn;
+use parking_lot::RwLock;
+
+lazy_static::lazy_static! {
+  static ref PSEUDO_CLASSES: RwLock<HashSet<&'static str>> = RwLock::new(HashSet::new());
+  static ref PSEUDO_ELEMENTS: RwLock<HashSet<&'static str>> = RwLock::new(HashSet::new());
+}
+
+/// Pseudo-class defined by the application for the states of its widgets,
+/// like `:selected` or `:expanded`.
+pub trait CustomPseudoClass: Copy + 'static {
+  /// All the pseudo-classes of the type.
+  const ALL: &'static [Self];
+
+  /// Name, without the colon.
+  fn name(self) -> &'static str;
+}
+
+/// Register the pseudo-classes of `T`, to be used in stylesheets parsed
+/// afterward. Selectors using unknown pseudo-classes are reported while
+/// parsing.
+pub fn register_pseudo_classes<T: CustomPseudoClass>() {
+  PSEUDO_CLASSES.write().extend(T::ALL.iter().map(|c| c.name()));
+}
+
+/// Whether `name` was registered with `register_pseudo_classes`.
+pub(crate) fn is_registered_pseudo_class(name: &str) -> bool {
+  PSEUDO_CLASSES.read().contains(name)
+}
+
+/// Pseudo-element defined by the application or a toolkit integration for
+/// the parts of a widget, like `slider::thumb`.
+pub trait CustomPseudoElement: Copy + 'static {
+  /// All the pseudo-elements of the type.
+  const ALL: &'static [Self];
+
+  /// Name, without the colons.
+  fn name(self) -> &'static str;
+}
+
+/// Register the pseudo-elements of `T`, to be used in stylesheets parsed
+/// afterward. Selectors using unknown pseudo-elements are reported while
+/// parsing.
+pub fn register_pseudo_elements<T: CustomPseudoElement>() {
+  PSEUDO_ELEMENTS.write().extend(T::ALL.iter().map(|e| e.name()));
+}
+
+/// Whether `name` was registered with `register_pseudo_elements`.
+pub(crate) fn is_registered_pseudo_element(name: &str) -> bool {
+  PSEUDO_ELEMENTS.read().contains(name)
+}
 
 #[derive(Debug, Default, PartialEq)]
 pub(crate) enum ElementName<'i> {
@@ -36,6 +87,12 @@ pub struct Element<'i> {
   pub(crate) classes: HashSet<&'i str>,
   pseudo_classes: Vec<PseudoClass<'i>>,
   attributes: HashMap<&'i str, &'i str>,
+  children: Vec<Element<'i>>,
+  /// Names of the element as a part of `originating`, matched by
+  /// `::part()`.
+  parts: Vec<&'i str>,
+  /// Element a pseudo-element or a part belongs to.
+  originating: Option<Box<Element<'i>>>,
 }
 
 impl<'i> std::fmt::Debug for Element<'i> {
@@ -46,7 +103,13 @@ impl<'i> std::fmt::Debug for Element<'i> {
 
 impl<'i> std::fmt::Display for Element<'i> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if let Some(originating) = &self.originating {
+      write!(f, "{originating}")?;
+    }
     write!(f, "{}", &self.name)?;
+    for part in &self.parts {
+      write!(f, "::part({part})")?;
+    }
     if let Some(id) = &self.id {
       write!(f, "#{id}")?;
     }
@@ -69,10 +132,30 @@ impl<'i> Element<'i> {
     Element::default()
   }
 
-  /// Pseudo element.
-  pub fn pseudo(pseudo: PseudoElement<'i>) -> Element<'i> {
+  /// Pseudo element of `originating`. The rest of the selector, like
+  /// `button:hover` in `button:hover::before`, is matched against
+  /// `originating`. Its properties inherit from the ones of `originating`,
+  /// see `Rules::compute_with_parent`.
+  pub fn pseudo(pseudo: PseudoElement<'i>, originating: Element<'i>) -> Element<'i> {
     Element {
       name: ElementName::Pseudo(pseudo),
+      originating: Some(Box::new(originating)),
+      ..Default::default()
+    }
+  }
+
+  /// Pseudo-element defined by the application, for a part of
+  /// `originating`. Matches against `originating::name {}`.
+  pub fn custom_pseudo<T: CustomPseudoElement>(pseudo: T, originating: Element<'i>) -> Element<'i> {
+    Element::pseudo(PseudoElement::Custom(pseudo.name().into()), originating)
+  }
+
+  /// Part `name` of the widget `host`. Matches against
+  /// `host::part(name) {}`.
+  pub fn part(name: &'i str, host: Element<'i>) -> Element<'i> {
+    Element {
+      parts: vec![name],
+      originating: Some(Box::new(host)),
       ..Default::default()
     }
   }
@@ -93,20 +176,14 @@ impl<'i> Element<'i> {
     }
   }
 
-  /// `::before` pseudo element.
-  pub fn before() -> Element<'i> {
-    Element {
-      name: ElementName::Pseudo(PseudoElement::Before),
-      ..Default::default()
-    }
+  /// `::before` pseudo element of `originating`.
+  pub fn before(originating: Element<'i>) -> Element<'i> {
+    Element::pseudo(PseudoElement::Before, originating)
   }
 
-  /// `::after` pseudo element.
-  pub fn after() -> Element<'i> {
-    Element {
-      name: ElementName::Pseudo(PseudoElement::After),
-      ..Default::default()
-    }
+  /// `::after` pseudo element of `originating`.
+  pub fn after(originating: Element<'i>) -> Element<'i> {
+    Element::pseudo(PseudoElement::After, originating)
   }
 
   /// Set element's id. Matches against `#name {}`.
@@ -132,37 +209,144 @@ impl<'i> Element<'i> {
     self.pseudo_classes.push(class);
     self
   }
+
+  /// Add a pseudo-class defined by the application to element. Matches
+  /// against `:name {}`.
+  pub fn state<T: CustomPseudoClass>(self, state: T) -> Element<'i> {
+    self.pseudo_class(PseudoClass::Custom(state.name().into()))
+  }
+
+  /// Value of the attribute `name`. Pseudo-elements have the attributes of
+  /// their originating element.
+  pub(crate) fn get_attribute(&self, name: &str) -> Option<&'i str> {
+    match (&self.name, &self.originating) {
+      (ElementName::Pseudo(_), Some(originating)) => originating.get_attribute(name),
+      _ => self.attributes.get(name).copied(),
+    }
+  }
+
+  /// Add child to element. Children are only used to match `:has()`.
+  pub fn child(mut self, child: Element<'i>) -> Element<'i> {
+    self.children.push(child);
+    self
+  }
+
+  /// Whether one of the relative selectors of `:has(selectors)` matches,
+  /// with the element as anchor.
+  /// See <https://drafts.csswg.org/selectors/#relational>
+  pub(crate) fn has(&self, selectors: &[Selector<'_, Selectors>]) -> bool {
+    let anchor = Node::new(self);
+    let mut context = MatchingContext::new(MatchingMode::Normal, None, None, QuirksMode::NoQuirks);
+    context.scope_element = Some(anchor.opaque());
+    selectors.iter().any(|selector| {
+      // `:has(> a)` is parsed as `:scope > a`, `:has(a)` as `a`.
+      let mut components = selector.iter_raw_match_order().rev();
+      let combinator = match (components.next(), components.next()) {
+        (Some(Component::Scope), Some(Component::Combinator(c))) => *c,
+        _ => Combinator::Descendant,
+      };
+      let mut matches = |node: &Node<'_, 'i>| matches_selector(selector, 0, None, node, &mut context, &mut |_, _| {});
+      match combinator {
+        Combinator::Child => anchor.children().any(|child| matches(&child)),
+        Combinator::Descendant => anchor.any_descendant(&mut matches),
+        // Sibling combinators are rejected when the stylesheet is parsed.
+        _ => false,
+      }
+    })
+  }
+}
+
+/// Element in a tree, as seen by selectors. Elements given to
+/// `Rules::compute` have no parent nor siblings, but their children can be
+/// matched by `:has()`.
+#[derive(Clone, Copy)]
+pub(crate) struct Node<'a, 'i> {
+  element: &'a Element<'i>,
+  parent: Option<&'a Node<'a, 'i>>,
+  /// Index in the children of the parent.
+  index: usize,
 }
 
-impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
+impl<'a, 'i> Node<'a, 'i> {
+  pub(crate) fn new(element: &'a Element<'i>) -> Node<'a, 'i> {
+    Node {
+      element,
+      parent: None,
+      index: 0,
+    }
+  }
+
+  fn children(&'a self) -> impl Iterator<Item = Node<'a, 'i>> {
+    self.element.children.iter().enumerate().map(move |(index, element)| {
+      Node {
+        element,
+        parent: Some(self),
+        index,
+      }
+    })
+  }
+
+  fn sibling(&self, index: usize) -> Option<Self> {
+    let parent = self.parent?;
+    parent.element.children.get(index).map(|element| {
+      Node {
+        element,
+        parent: Some(parent),
+        index,
+      }
+    })
+  }
+
+  /// Whether `f` is true for one of the descendants of the node, in tree
+  /// order.
+  fn any_descendant(&self, f: &mut dyn FnMut(&Node<'_, 'i>) -> bool) -> bool {
+    self.children().any(|child| f(&child) || child.any_descendant(f))
+  }
+}
+
+impl<'a, 'i> std::fmt::Debug for Node<'a, 'i> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.element)
+  }
+}
+
+impl<'i, 'a, 'b> parcel_selectors::Element<'i> for Node<'b, 'a> {
   type Impl = Selectors;
 
   fn opaque(&self) -> OpaqueElement {
-    OpaqueElement::new(&self)
+    OpaqueElement::new(self.element)
   }
 
   fn parent_element(&self) -> Option<Self> {
-    None
+    self.parent.copied()
   }
 
   fn parent_node_is_shadow_root(&self) -> bool {
     false
   }
 
+  /// Parts are in the shadow tree of their widget.
   fn containing_shadow_host(&self) -> Option<Self> {
-    None
+    match &self.element.originating {
+      Some(host) if !self.element.parts.is_empty() => Some(Node::new(host)),
+      _ => None,
+    }
+  }
+
+  fn pseudo_element_originating_element(&self) -> Option<Self> {
+    self.element.originating.as_deref().map(Node::new)
   }
 
   fn is_pseudo_element(&self) -> bool {
-    matches!(self.name, ElementName::Pseudo(_))
+    matches!(self.element.name, ElementName::Pseudo(_))
   }
 
   fn prev_sibling_element(&self) -> Option<Self> {
-    None
+    self.sibling(self.index.checked_sub(1)?)
   }
 
   fn next_sibling_element(&self) -> Option<Self> {
-    None
+    self.sibling(self.index + 1)
   }
 
   fn is_html_element_in_html_document(&self) -> bool {
@@ -170,7 +354,7 @@ impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
   }
 
   fn has_local_name(&self, local_name: &SelectorIdent<'_>) -> bool {
-    if let ElementName::Named(name) = self.name {
+    if let ElementName::Named(name) = self.element.name {
       name == local_name.0.as_ref()
     } else {
       false
@@ -184,7 +368,7 @@ impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
   fn is_same_type(&self, other: &Self) -> bool {
     // Whether this element and the other element have the same local name and
     // namespace.
-    other.name == self.name
+    other.element.name == self.element.name
   }
 
   fn attr_matches(
@@ -193,25 +377,16 @@ impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
     name: &SelectorIdent<'_>,
     operation: &AttrSelectorOperation<&SelectorString<'_>>,
   ) -> bool {
-    let value = self.attributes.get(name.0.as_ref());
+    let value = self.element.attributes.get(name.0.as_ref());
     match operation {
       AttrSelectorOperation::Exists => value.is_some(),
+      // `=`, `~=`, `|=`, `^=`, `$=` and `*=`, with the `i` and `s` flags.
+      // See https://developer.mozilla.org/en-US/docs/Web/CSS/Attribute_selectors
       AttrSelectorOperation::WithValue {
         operator,
-        case_sensitivity: _,
+        case_sensitivity,
         expected_value,
-      } => {
-        let expected_value = expected_value.0.as_ref();
-        // See https://developer.mozilla.org/en-US/docs/Web/CSS/Attribute_selectors
-        #[allow(clippy::single_match_else)]
-        match operator {
-          AttrSelectorOperator::Equal => value.map_or(false, |v| *v == expected_value),
-          _ => {
-            warn!("Unsupported selector");
-            false
-          },
-        }
-      },
+      } => value.map_or(false, |v| operator.eval_str(v, expected_value.0.as_ref(), *case_sensitivity)),
     }
   }
 
@@ -226,7 +401,7 @@ impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
     };
     // This exist because we can't use PartialEq (==) between 2 elements of same
     // lifetime.
-    self.pseudo_classes.iter().any(|a| {
+    self.element.pseudo_classes.iter().any(|a| {
       match (a, pc) {
         (Hover, Hover) => true,
         (Active, Active) => true,
@@ -283,8 +458,10 @@ impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
 
   #[allow(clippy::match_same_arms)]
   fn match_pseudo_element(&self, pe: &PseudoElement<'i>, _context: &mut MatchingContext<'_, '_, Self::Impl>) -> bool {
-    use PseudoElement::{After, Backdrop, Before, Cue, CueRegion, FileSelectorButton, FirstLetter, FirstLine, Marker, Placeholder, Selection, WebKitScrollbar};
-    match &self.name {
+    use PseudoElement::{
+      After, Backdrop, Before, Cue, CueRegion, Custom, FileSelectorButton, FirstLetter, FirstLine, Marker, Placeholder, Selection, WebKitScrollbar,
+    };
+    match &self.element.name {
       // This exist because we can't use PartialEq (==) between 2 elements of same lifetime.
       ElementName::Pseudo(elt) => {
         match (elt, pe) {
@@ -300,6 +477,7 @@ impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
           (Backdrop(a), Backdrop(b)) => a == b,
           (FileSelectorButton(a), FileSelectorButton(b)) => a == b,
           (WebKitScrollbar(a), WebKitScrollbar(b)) => a == b,
+          (Custom(a), Custom(b)) => a == b,
           _ => false,
         }
       },
@@ -317,27 +495,27 @@ impl<'i, 'a> parcel_selectors::Element<'i> for &Element<'a> {
 
   fn has_id(&self, id: &SelectorIdent<'_>, _: CaseSensitivity) -> bool {
     // Not quirks mode. Always case sensitivie
-    self.id.as_ref().map_or(false, |i| *i == id.0.as_ref())
+    self.element.id.as_ref().map_or(false, |i| *i == id.0.as_ref())
   }
 
   fn has_class(&self, name: &SelectorIdent<'_>, _: CaseSensitivity) -> bool {
     // Not quirks mode. Always case sensitivie
-    self.classes.contains(name.0.as_ref())
+    self.element.classes.contains(name.0.as_ref())
   }
 
   fn imported_part(&self, _name: &SelectorIdent<'_>) -> Option<SelectorIdent<'i>> {
     None
   }
 
-  fn is_part(&self, _name: &SelectorIdent<'_>) -> bool {
-    false
+  fn is_part(&self, name: &SelectorIdent<'_>) -> bool {
+    self.element.parts.contains(&name.0.as_ref())
   }
 
   fn is_empty(&self) -> bool {
-    true
+    self.element.children.is_empty()
   }
 
   fn is_root(&self) -> bool {
-    self.is_root
+    self.element.is_root
   }
 }
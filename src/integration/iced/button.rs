@@ -1,17 +1,22 @@
 use iced::widget::button;
-use iced::{Background, Vector};
-use iced_native::Length;
+use iced::Vector;
 
 use crate::element::{Element, PseudoClass};
+use crate::integration::iced::converters::DEFAULT_FONT_SIZE;
 use crate::integration::iced::shared_rules::SharedRules;
 use crate::integration::iced::{IdAndClasses, CSS};
-use crate::properties::ComputedProperties;
+use crate::properties::{ComputedProperties, Display, Length};
 
 /// 1. `CSS::button` constructor (with layout style)
 /// 2. `button::StyleSheet` implementation (non-layout style)
 /// 3. `ComputedProperties` to `button::Appearance`
 
 impl CSS {
+  /// Button styled as the `button` element `def`.
+  ///
+  /// A `Button` can't be removed from here: views must skip buttons that
+  /// are `display: none` with [`CSS::is_displayed`]. Otherwise they get an
+  /// empty size, but their content is still laid out.
   pub fn button<'a, Message, Renderer>(
     &self,
     content: impl Into<iced::Element<'a, Message, Renderer>>,
@@ -22,18 +27,21 @@ impl CSS {
   {
     let elt = Element::def("button", &def);
     let props = self.rules.compute(&elt);
-    #[allow(clippy::match_same_arms)]
+    if props.display == Display::None {
+      // Takes no space, for callers that didn't check `CSS::is_displayed`.
+      let none = iced_native::Length::Units(0);
+      return iced::widget::Button::new(content).padding(0).width(none).height(none).style(def);
+    }
     let width = match (props.flex_grow as u16, props.flex_basis, props.width) {
-      (0, None, None) => Length::Shrink,
-      (0, None, Some(w)) => Length::Units(w as u16),
-      (0, Some(w), _) => Length::Units(w as u16),
-      (grow, _, _) => Length::FillPortion(grow),
-    };
-    let height = match props.height {
-      None => Length::Shrink,
-      Some(h) => Length::Units(h as u16),
+      (0, Length::Auto, w) => w.into(),
+      (0, basis, _) => basis.into(),
+      (grow, _, _) => iced_native::Length::FillPortion(grow),
     };
-    iced::widget::Button::new(content).padding(props.padding).width(width).height(height).style(def)
+    iced::widget::Button::new(content)
+      .padding(props.padding)
+      .width(width)
+      .height(props.height.into())
+      .style(def)
   }
 }
 
@@ -64,13 +72,32 @@ impl button::StyleSheet for SharedRules {
 impl From<ComputedProperties> for button::Appearance {
   fn from(c: ComputedProperties) -> button::Appearance {
     button::Appearance {
-      background: c.background_color.to_opt().map(|c| Background::Color(c.into())),
-      border_radius: c.border_radius.nw.0,
-      border_width: c.border.top.width,
-      border_color: c.border.top.color.into(),
-      text_color: c.color.into(),
-      // FIXME: unmapped
-      shadow_offset: Vector::default(),
+      background: c.iced_background(),
+      border_radius: border_radius(&c),
+      border_width: c.border_widths().top,
+      border_color: c.iced_color(c.border.top.color),
+      text_color: c.iced_color(c.color),
+      // iced only draws an offset, from the front-most outer shadow.
+      shadow_offset: c.box_shadow.iter().find(|s| !s.inset).map_or_else(Vector::default, |s| Vector::new(s.x, s.y)),
     }
   }
 }
+
+/// iced has a single radius, and doesn't give the size of the button to its
+/// style. Percentages are approximated against the height of the button:
+/// its fixed height, or the height of its text and vertical padding.
+fn border_radius(c: &ComputedProperties) -> f32 {
+  match c.border_radius.nw.0 {
+    Length::Px(r) => r,
+    // iced clamps the radius to half the smallest side.
+    Length::Percent(p) if p >= 50.0 => f32::INFINITY,
+    Length::Percent(p) => {
+      let height = match c.height {
+        Length::Px(h) => h,
+        _ => c.font_size.unwrap_or(DEFAULT_FONT_SIZE) + f32::from(c.padding.top.to_units().saturating_add(c.padding.bottom.to_units())),
+      };
+      p * height / 100.0
+    },
+    r => f32::from(r.to_units()),
+  }
+}
@@ -1,23 +1,54 @@
-use crate::properties::{Align, Color, Sides};
+use iced::Background;
+
+use crate::properties::{Align, Color, ComputedProperties, CursorKeyword, Image, Length, Sides};
+
+/// Iced's default text size, used for lengths relative to an unknown font size.
+pub(crate) const DEFAULT_FONT_SIZE: f32 = 20.0;
 
 impl From<Color> for iced::Color {
   fn from(c: Color) -> iced::Color {
     iced::Color {
-      r: c.r as f32 / 255.0,
-      g: c.g as f32 / 255.0,
-      b: c.b as f32 / 255.0,
-      a: c.a as f32 / 255.0,
+      r: c.r.clamp(0.0, 1.0),
+      g: c.g.clamp(0.0, 1.0),
+      b: c.b.clamp(0.0, 1.0),
+      a: c.a.clamp(0.0, 1.0),
+    }
+  }
+}
+
+impl Length {
+  /// Length in pixels. Lengths relative to a size Iced doesn't expose are
+  /// zero.
+  pub(crate) fn to_units(self) -> u16 {
+    match self {
+      Length::Px(v) => v as u16,
+      Length::Em(v) => (v * DEFAULT_FONT_SIZE) as u16,
+      Length::Calc(sum) => sum.to_px(DEFAULT_FONT_SIZE, 0.0, (0.0, 0.0)) as u16,
+      Length::Percent(_) | Length::Auto | Length::MinContent | Length::MaxContent | Length::FitContent => 0,
     }
   }
 }
 
-impl From<Sides<f32>> for iced::Padding {
-  fn from(s: Sides<f32>) -> iced::Padding {
+impl From<Length> for iced_native::Length {
+  fn from(l: Length) -> Self {
+    match l {
+      Length::Px(_) | Length::Em(_) | Length::Calc(_) => Self::Units(l.to_units()),
+      Length::Percent(p) if p >= 100.0 => Self::Fill,
+      // Iced shares the available space between siblings, proportionally to
+      // their portion. A portion of 0 would take no space at all.
+      Length::Percent(p) => Self::FillPortion((p.round() as u16).max(1)),
+      Length::Auto | Length::MinContent | Length::MaxContent | Length::FitContent => Self::Shrink,
+    }
+  }
+}
+
+impl From<Sides<Length>> for iced::Padding {
+  fn from(s: Sides<Length>) -> iced::Padding {
     iced::Padding {
-      top: s.top as u16,
-      right: s.right as u16,
-      bottom: s.bottom as u16,
-      left: s.left as u16,
+      top: s.top.to_units(),
+      right: s.right.to_units(),
+      bottom: s.bottom.to_units(),
+      left: s.left.to_units(),
     }
   }
 }
@@ -45,3 +76,55 @@ impl From<Align> for iced_native::alignment::Vertical {
     }
   }
 }
+
+/// This revision of Iced has no cursor images, and fewer cursors than CSS.
+impl From<CursorKeyword> for iced_native::mouse::Interaction {
+  fn from(k: CursorKeyword) -> Self {
+    use CursorKeyword as K;
+    match k {
+      K::Pointer => Self::Pointer,
+      K::Grab | K::Move | K::AllScroll => Self::Grab,
+      K::Grabbing => Self::Grabbing,
+      K::Text | K::VerticalText => Self::Text,
+      K::Crosshair | K::Cell => Self::Crosshair,
+      K::Wait | K::Progress => Self::Working,
+      K::EResize | K::WResize | K::EwResize | K::ColResize => Self::ResizingHorizontally,
+      K::NResize | K::SResize | K::NsResize | K::RowResize => Self::ResizingVertically,
+      _ => Self::Idle,
+    }
+  }
+}
+
+impl ComputedProperties {
+  /// `c` with the opacity of the element applied. Transparent if the
+  /// element is not visible.
+  pub(crate) fn iced_color(&self, c: Color) -> iced::Color {
+    let mut color: iced::Color = c.into();
+    color.a *= if self.is_visible() { self.opacity } else { 0.0 };
+    color
+  }
+
+  /// Cursor over the element. Elements that don't receive pointer events
+  /// leave the cursor to what is below them.
+  pub(crate) fn iced_interaction(&self) -> iced_native::mouse::Interaction {
+    if self.is_interactive() {
+      self.cursor.keyword.into()
+    } else {
+      iced_native::mouse::Interaction::Idle
+    }
+  }
+
+  /// This revision of Iced only draws solid backgrounds: a gradient is
+  /// approximated by its first color, images are ignored.
+  pub(crate) fn iced_background(&self) -> Option<Background> {
+    let gradient = self.background_layers().into_iter().find_map(|layer| {
+      match layer.image {
+        Image::Gradient(g) => g.stops.first().map(|stop| stop.color),
+        Image::Url(_) => None,
+      }
+    });
+    gradient
+      .or_else(|| self.background_color.to_opt())
+      .map(|c| Background::Color(self.iced_color(c)))
+  }
+}
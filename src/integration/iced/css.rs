@@ -5,10 +5,13 @@ use anyhow::Result;
 use iced::futures::channel::mpsc;
 use iced::futures::{FutureExt, SinkExt, StreamExt, TryFutureExt};
 use iced::subscription::{self, Subscription};
+use iced_native::mouse;
 
+use crate::element::Element;
 use crate::integration::iced::shared_rules::SharedRules;
-use crate::integration::iced::CssEvent;
+use crate::integration::iced::{CssEvent, IdAndClasses};
 use crate::parser::{parse_file, parse_file_sync, Event};
+use crate::properties::Display;
 
 pub struct CSS {
   pub(crate) rules: SharedRules,
@@ -40,6 +43,18 @@ impl CSS {
     self.rules.clone()
   }
 
+  /// Whether the widget named `name` takes space: views should skip it when
+  /// it is `display: none`.
+  pub fn is_displayed(&self, name: &str, def: IdAndClasses) -> bool {
+    self.rules.compute(&Element::def(name, &def)).display != Display::None
+  }
+
+  /// Cursor for custom widgets, to return from `Widget::mouse_interaction`
+  /// when the widget is hovered.
+  pub fn mouse_interaction(&self, name: &str, def: IdAndClasses) -> mouse::Interaction {
+    self.rules.compute(&Element::def(name, &def)).iced_interaction()
+  }
+
   pub fn subscription(&self) -> Subscription<CssEvent> {
     let receiver = self.receiver.replace(None);
     struct Sub;
@@ -7,20 +7,36 @@ use crate::element::Element;
 use crate::parser::Rules;
 use crate::properties::ComputedProperties;
 
+/// Rules, with the computed properties of `:root` cached alongside them.
+struct Inner {
+  rules: Rules,
+  root: ComputedProperties,
+}
+
+impl Inner {
+  fn new(rules: Rules) -> Inner {
+    let root = rules.compute(&Element::root());
+    Inner { rules, root }
+  }
+}
+
 #[derive(Clone)]
-pub struct SharedRules(Arc<RwLock<Rules>>);
+pub struct SharedRules(Arc<RwLock<Inner>>);
 
 impl SharedRules {
   pub(crate) fn new(rules: Rules) -> SharedRules {
-    SharedRules(Arc::new(RwLock::new(rules)))
+    SharedRules(Arc::new(RwLock::new(Inner::new(rules))))
   }
 
   pub(crate) fn update(&self, rules: Rules) {
-    *self.0.write() = rules;
+    *self.0.write() = Inner::new(rules);
   }
 
+  /// Widgets are computed as children of `:root`, so inherited properties
+  /// and variables set on `:root` apply to all widgets.
   pub(crate) fn compute(&self, elt: &Element<'_>) -> ComputedProperties {
-    self.0.read().compute(elt)
+    let inner = self.0.read();
+    inner.rules.compute_with_parent(elt, &inner.root)
   }
 }
 
@@ -34,7 +50,6 @@ impl application::StyleSheet for SharedRules {
   type Style = ();
 
   fn appearance(&self, _: Self::Style) -> application::Appearance {
-    let elt = Element::root();
-    self.compute(&elt).into()
+    self.0.read().root.clone().into()
   }
 }
@@ -1,7 +1,6 @@
 use std::borrow::Cow;
 
 use iced::widget::text;
-use iced_native::Length;
 
 use crate::element::Element;
 use crate::integration::iced::shared_rules::SharedRules;
@@ -25,27 +24,19 @@ impl CSS {
     // fn height(self, height: Length)
     // fn horizontal_alignment(self, alignment: Horizontal) -> Self
     // fn vertical_alignment(self, alignment: Vertical) -> Self
-    let mut text = iced::widget::Text::new(content);
+    let mut text = iced::widget::Text::new(props.text_transform.transform(content));
 
     if let Some(size) = props.font_size {
       text = text.size(size as u16);
     }
 
-    // FIXME: missing font
-
-    let height = match props.height {
-      None => Length::Shrink,
-      Some(h) => Length::Units(h as u16),
-    };
-
-    let width = match props.width {
-      None => Length::Shrink,
-      Some(w) => Length::Units(w as u16),
-    };
+    // FIXME: missing font. iced only loads `'static` font bytes, so
+    // `font_family`, `font_weight`, `font_style` and `Rules::font_faces` are
+    // not applied.
 
     text
-      .width(width)
-      .height(height)
+      .width(props.width.into())
+      .height(props.height.into())
       .horizontal_alignment(props.text_align.into())
       .vertical_alignment(props.vertical_align.into())
       .style(def)
@@ -64,7 +55,7 @@ impl text::StyleSheet for SharedRules {
 impl From<ComputedProperties> for text::Appearance {
   fn from(c: ComputedProperties) -> text::Appearance {
     text::Appearance {
-      color: c.color.to_opt().map(|c| c.into()),
+      color: c.color.to_opt().map(|color| c.iced_color(color)),
     }
   }
 }
@@ -14,7 +14,11 @@
 //! - Computed properties are exported to a generic format that can be use with
 //!   any toolkit. It also supports exporting to toolkit-specific style
 //!   structures;
-//! - CSS variables are supported;
+//! - CSS variables are supported, anywhere in a value, with fallbacks;
+//! - Inherited properties and CSS variables flow from parent to children;
+//! - Relative lengths and `calc()` are resolved against a layout context;
+//! - Applications can register pseudo-classes for the states of their widgets
+//!   (`:selected`) and pseudo-elements for their parts (`slider::thumb`);
 //!
 //! # CSS example
 //! ```css
@@ -25,6 +29,8 @@
 #![doc = include_str!("../examples/winit.rs")]
 //! ```
 
+/// Transitions and animations of computed properties.
+pub mod animation;
 mod compute;
 /// Elements matched against selectors.
 pub mod element;
@@ -36,6 +42,8 @@ pub mod parser;
 /// Parsed and computed properties.
 pub mod properties;
 mod themes;
+mod units;
+mod variables;
 
 #[cfg(feature = "toolkit-iced")]
 pub use iced;
@@ -44,12 +52,33 @@ pub use iced_native;
 
 #[cfg(test)]
 mod tests {
-  use crate::element::Element;
+  use lightningcss::values::syntax::ParsedComponent;
+
+  use crate::animation::Transition;
+  use crate::element::{
+    is_registered_pseudo_class, is_registered_pseudo_element, register_pseudo_classes, register_pseudo_elements, CustomPseudoClass, CustomPseudoElement,
+    Element, PseudoClass,
+  };
   use crate::parser::parse_string_sync as parse;
-  use crate::properties::{Color, ComputedProperties};
+  use crate::properties::{
+    Align, AnimationDirection, BackgroundSize, BorderStyle, CalcLength, Color, ComputedProperties, ContentAlign, ContentItem, Context, CursorKeyword,
+    Direction, Display, FillMode, FontFamily, FontSource, FontStyle, GeneratedContent, GradientShape, GridLine, GridPlacement, Image, ItemAlign,
+    IterationCount, Length, LineHeight, PointerEvents, Position, Repeat, Shadow, Sides, TextOverflow, TextTransform, TextWrap, TimingFunction, Track,
+    TrackBreadth, TrackSize, TransitionProperty, Visibility, Wrap,
+  };
   use crate::themes::{set_theme, SystemTheme};
-  const RED_COLOR: Color = Color { r: 255, g: 0, b: 0, a: 255 };
-  const GREEN_COLOR: Color = Color { r: 0, g: 128, b: 0, a: 255 };
+  const RED_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+  };
+  const GREEN_COLOR: Color = Color {
+    r: 0.0,
+    g: 128.0 / 255.0,
+    b: 0.0,
+    a: 1.0,
+  };
 
   fn green_prop() -> ComputedProperties {
     ComputedProperties {
@@ -126,4 +155,1128 @@ mod tests {
     assert_eq!(r1.compute(&elt1), red_prop());
     assert_eq!(r2.compute(&elt2), green_prop());
   }
+
+  #[test]
+  fn attribute_operators() {
+    let matches = |selector: &str, value: &str| {
+      let rules = parse(&format!("button{selector} {{ color: red; }}"), None).unwrap();
+      rules.compute(&Element::named("button").attribute("data-kind", value)).color == RED_COLOR
+    };
+    assert!(matches(r#"[data-kind^="danger"]"#, "danger-zone"));
+    assert!(!matches(r#"[data-kind^="danger"]"#, "no-danger"));
+    assert!(matches(r#"[data-kind$="zone"]"#, "danger-zone"));
+    assert!(matches(r#"[data-kind*="ger-z"]"#, "danger-zone"));
+    assert!(matches(r#"[data-kind~="primary"]"#, "large primary"));
+    assert!(!matches(r#"[data-kind~="primary"]"#, "large-primary"));
+    assert!(matches(r#"[data-kind|="en"]"#, "en-US"));
+    assert!(!matches(r#"[data-kind|="en"]"#, "english"));
+    assert!(!matches(r#"[data-kind="Danger"]"#, "danger"));
+    assert!(matches(r#"[data-kind="Danger" i]"#, "danger"));
+    assert!(matches(r#"[data-kind^="DANGER" i]"#, "danger-zone"));
+    assert!(!matches(r#"[data-kind="Danger" s]"#, "danger"));
+  }
+
+  #[test]
+  fn inheritance() {
+    let source = r#"
+    :root {
+      --accent: red;
+    }
+    vbox {
+      color: green;
+      font-size: 20px;
+      padding: 4px;
+    }
+    hbox {
+      background-color: var(--accent);
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+
+    let root = rules.compute(&Element::root());
+    let vbox = rules.compute_with_parent(&Element::named("vbox"), &root);
+    let hbox = rules.compute_with_parent(&Element::named("hbox"), &vbox);
+    assert_eq!(hbox.color, GREEN_COLOR);
+    assert_eq!(hbox.font_size, Some(20.0));
+    assert_eq!(hbox.padding, Sides::default());
+    assert_eq!(hbox.background_color, RED_COLOR);
+
+    // Without a parent, nothing is inherited.
+    let hbox = rules.compute(&Element::named("hbox"));
+    assert_eq!(hbox.color, Color::default());
+    assert_eq!(hbox.background_color, Color::default());
+  }
+
+  #[test]
+  fn wide_keywords() {
+    let source = r#"
+    vbox {
+      color: green;
+      padding: 4px;
+      --accent: green;
+    }
+    hbox {
+      color: red;
+      padding: 8px;
+      background-color: red;
+      --accent: red;
+    }
+    hbox.inherit {
+      color: inherit;
+      padding: inherit;
+      --accent: inherit;
+    }
+    hbox.initial {
+      color: initial;
+      padding-left: initial;
+      --accent: initial;
+    }
+    hbox.unset {
+      color: unset;
+      padding: unset;
+    }
+    hbox.all {
+      all: initial;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let vbox = rules.compute(&Element::named("vbox"));
+    let compute = |class| rules.compute_with_parent(&Element::named("hbox").class(class), &vbox);
+
+    let inherit = compute("inherit");
+    assert_eq!(inherit.color, GREEN_COLOR);
+    assert_eq!(inherit.padding.top, Length::Px(4.0));
+    assert_eq!(inherit.variables.get("--accent"), vbox.variables.get("--accent"));
+
+    let initial = compute("initial");
+    assert_eq!(initial.color, Color::default());
+    assert_eq!(initial.padding.left, Length::Px(0.0));
+    assert_eq!(initial.padding.top, Length::Px(8.0));
+    assert_eq!(initial.variables.get("--accent"), None);
+
+    let unset = compute("unset");
+    assert_eq!(unset.color, GREEN_COLOR);
+    assert_eq!(unset.padding, Sides::default());
+
+    let all = compute("all");
+    assert_eq!(all.color, Color::default());
+    assert_eq!(all.background_color, Color::default());
+    assert_eq!(all.padding, Sides::default());
+    assert_eq!(all.variables, rules.compute(&Element::named("hbox")).variables);
+  }
+
+  #[test]
+  fn variables() {
+    let source = r#"
+    :root {
+      --gap: 4px;
+      --line: green;
+      --nested: var(--gap);
+      --a: var(--b, var(--c, red));
+      --cycle-a: var(--cycle-b);
+      --cycle-b: var(--cycle-a, 1px);
+    }
+    hbox {
+      padding: 2px var(--gap);
+      margin: var(--nested) var(--missing);
+      border: 1px solid var(--line);
+      color: var(--a);
+      background-color: var(--cycle-a, green);
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let root = rules.compute(&Element::root());
+    let hbox = rules.compute_with_parent(&Element::named("hbox"), &root);
+
+    assert_eq!(
+      hbox.padding,
+      Sides {
+        top: Length::Px(2.0),
+        right: Length::Px(4.0),
+        bottom: Length::Px(2.0),
+        left: Length::Px(4.0)
+      }
+    );
+    assert_eq!(hbox.margin, Sides::default());
+    assert_eq!(hbox.border.left.width, 1.0);
+    assert_eq!(hbox.border.left.color, GREEN_COLOR);
+    assert_eq!(hbox.color, RED_COLOR);
+    assert_eq!(hbox.background_color, GREEN_COLOR);
+    assert!(hbox.variables.contains_key("--nested"));
+    assert!(!hbox.variables.contains_key("--cycle-a"));
+    assert!(!hbox.variables.contains_key("--cycle-b"));
+  }
+
+  #[test]
+  fn registered_variables() {
+    let source = r#"
+    @property --accent {
+      syntax: '<color>';
+      inherits: true;
+      initial-value: #36c;
+    }
+    @property --gap {
+      syntax: '<length>';
+      inherits: false;
+      initial-value: 4px;
+    }
+    :root {
+      --gap: 8px;
+    }
+    hbox {
+      color: var(--accent);
+      padding: var(--gap);
+    }
+    hbox.invalid {
+      --accent: 12px;
+      --gap: red;
+    }
+    hbox.valid {
+      --accent: green;
+    }
+    hbox.trailing {
+      --gap: 2px solid;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let root = rules.compute(&Element::root());
+    let compute = |elt| rules.compute_with_parent(&elt, &root);
+    let accent = Color::from_rgb8(51, 102, 204);
+
+    let hbox = compute(Element::named("hbox"));
+    assert_eq!(hbox.color, accent);
+    assert_eq!(hbox.padding.top, Length::Px(4.0));
+
+    let invalid = compute(Element::named("hbox").class("invalid"));
+    assert_eq!(invalid.color, accent);
+    assert_eq!(invalid.padding.top, Length::Px(4.0));
+
+    let valid = compute(Element::named("hbox").class("valid"));
+    assert_eq!(valid.color, GREEN_COLOR);
+    assert!(matches!(valid.registered_variables.get("--accent"), Some(ParsedComponent::Color(_))));
+
+    let trailing = compute(Element::named("hbox").class("trailing"));
+    assert_eq!(trailing.padding.top, Length::Px(4.0));
+  }
+
+  #[test]
+  fn relative_lengths() {
+    let source = r#"
+    vbox {
+      font-size: 20px;
+    }
+    hbox {
+      --gap: 4px;
+      font-size: 1.5em;
+      padding: 1em 2rem 10vw 12pt;
+      width: calc(100% - 2 * var(--gap));
+      height: 50%;
+    }
+    label {
+      all: initial;
+      margin: 1em;
+    }
+    label.big {
+      font-size: 40px;
+    }
+    hbox.dynamic {
+      height: 10dvh;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let context = Context {
+      root_font_size: 16.0,
+      viewport: Some((1000.0, 500.0)),
+      containing_block: Some((200.0, 100.0)),
+      ..Context::default()
+    };
+    let vbox = rules.compute_in_context(&Element::named("vbox"), None, &context);
+    let hbox = rules.compute_in_context(&Element::named("hbox"), Some(&vbox), &context);
+    assert_eq!(hbox.font_size, Some(30.0));
+    assert_eq!(
+      hbox.padding,
+      Sides {
+        top: Length::Px(30.0),
+        right: Length::Px(32.0),
+        bottom: Length::Px(100.0),
+        left: Length::Px(16.0)
+      }
+    );
+    assert_eq!(hbox.width, Length::Px(192.0));
+    assert_eq!(hbox.height, Length::Px(50.0));
+    // Units that can't be resolved are skipped.
+    let dynamic = rules.compute_in_context(&Element::named("hbox").class("dynamic"), Some(&vbox), &context);
+    assert_eq!(dynamic.height, Length::Px(50.0));
+
+    // The winning font size applies to the whole cascade, and survives an
+    // `all` of a less specific rule.
+    let label = rules.compute_in_context(&Element::named("label").class("big"), Some(&vbox), &context);
+    assert_eq!(label.font_size, Some(40.0));
+    assert_eq!(label.margin.top, Length::Px(40.0));
+
+    // Percentages are kept without a containing block, and `calc()` as a
+    // sum. Font-relative lengths use the default root font size.
+    let hbox = rules.compute(&Element::named("hbox"));
+    assert_eq!(hbox.height, Length::Percent(50.0));
+    assert_eq!(hbox.padding.top, Length::Px(24.0));
+    assert_eq!(
+      hbox.width,
+      Length::Calc(CalcLength {
+        px: -8.0,
+        percent: 100.0,
+        ..CalcLength::default()
+      })
+    );
+  }
+
+  #[test]
+  fn typed_lengths() {
+    let source = r#"
+    hbox {
+      margin: 0 auto;
+      width: fit-content;
+      min-height: min-content;
+      max-width: 50%;
+      flex-basis: 25%;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let hbox = rules.compute(&Element::named("hbox"));
+    assert_eq!(
+      hbox.margin,
+      Sides {
+        top: Length::Px(0.0),
+        right: Length::Auto,
+        bottom: Length::Px(0.0),
+        left: Length::Auto
+      }
+    );
+    assert_eq!(hbox.width, Length::FitContent);
+    assert_eq!(hbox.height, Length::Auto);
+    assert_eq!(hbox.min_height, Length::MinContent);
+    assert_eq!(hbox.max_width, Some(Length::Percent(50.0)));
+    assert_eq!(hbox.max_height, None);
+    assert_eq!(hbox.flex_basis, Length::Percent(25.0));
+  }
+
+  #[test]
+  fn flexbox() {
+    let source = r#"
+    vbox {
+      flex-flow: column-reverse wrap;
+      place-content: center space-between;
+      align-items: baseline;
+      gap: 4px 10%;
+    }
+    button {
+      flex: 2 0 10px;
+      align-self: flex-end;
+      order: -1;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let vbox = rules.compute_in_context(
+      &Element::named("vbox"),
+      None,
+      &Context {
+        containing_block: Some((200.0, 100.0)),
+        ..Context::default()
+      },
+    );
+    assert_eq!(vbox.direction, Direction::VerticalReverse);
+    assert_eq!(vbox.wrap, Wrap::Wrap);
+    assert_eq!(vbox.align_content, ContentAlign::Center);
+    assert_eq!(vbox.justify_content, ContentAlign::SpaceBetween);
+    assert_eq!(vbox.align_items, ItemAlign::Baseline);
+    assert_eq!(vbox.row_gap, Length::Px(4.0));
+    assert_eq!(vbox.column_gap, Length::Px(20.0));
+    assert_eq!(vbox.align_self, None);
+    assert_eq!(vbox.flex_shrink, 1.0);
+
+    let button = rules.compute_with_parent(&Element::named("button"), &vbox);
+    assert_eq!(button.flex_grow, 2.0);
+    assert_eq!(button.flex_shrink, 0.0);
+    assert_eq!(button.flex_basis, Length::Px(10.0));
+    assert_eq!(button.align_self, Some(ItemAlign::FlexEnd));
+    assert_eq!(button.order, -1);
+  }
+
+  #[test]
+  fn grid() {
+    let source = r#"
+    form {
+      display: grid;
+      grid-template-columns: [labels] max-content repeat(2, minmax(100px, 1fr));
+      grid-template-areas: "title title title" "label field field";
+      place-items: center start;
+    }
+    label {
+      grid-area: label;
+      justify-self: end;
+    }
+    button {
+      grid-column: 2 / span 2;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let form = rules.compute(&Element::named("form"));
+    assert_eq!(form.display, Display::Grid);
+    let columns = &form.grid_template_columns;
+    assert_eq!(columns.line_names[0], vec!["labels".to_owned()]);
+    assert_eq!(columns.tracks[0], Track::Size(TrackSize::Breadth(TrackBreadth::MaxContent)));
+    match &columns.tracks[1] {
+      Track::Repeat { sizes, .. } => {
+        assert_eq!(
+          sizes,
+          &vec![TrackSize::MinMax(TrackBreadth::Length(Length::Px(100.0)), TrackBreadth::Fraction(1.0))]
+        )
+      },
+      track => panic!("Unexpected track: {track:?}"),
+    }
+    assert_eq!(form.align_items, ItemAlign::Center);
+    assert_eq!(form.justify_items, ItemAlign::Start);
+
+    let areas = form.grid_template_areas.unwrap();
+    let (rows, columns) = areas.lines("field").unwrap();
+    assert_eq!(rows.start, GridLine::Line(2, None));
+    assert_eq!(columns.start, GridLine::Line(2, None));
+    assert_eq!(columns.end, GridLine::Line(4, None));
+
+    let label = rules.compute(&Element::named("label"));
+    assert_eq!(label.grid_row.start, GridLine::Area("label".to_owned()));
+    assert_eq!(label.justify_self, Some(ItemAlign::End));
+
+    let button = rules.compute(&Element::named("button"));
+    assert_eq!(
+      button.grid_column,
+      GridPlacement {
+        start: GridLine::Line(2, None),
+        end: GridLine::Span(2, None),
+      }
+    );
+  }
+
+  #[test]
+  fn fonts() {
+    let source = r#"
+    @font-face {
+      font-family: "Inter";
+      src: url(fonts/inter.woff2) format("woff2"), local(Inter);
+      font-weight: 100 900;
+    }
+    vbox {
+      font: italic bold 16px/1.5 Inter, system-ui;
+      letter-spacing: 0.5em;
+    }
+    label {
+      font-weight: bolder;
+      line-height: 150%;
+    }
+    "#;
+
+    let rules = parse(source, Some(std::path::Path::new("/themes/app.css"))).unwrap();
+    let faces = rules.font_faces();
+    assert_eq!(faces.len(), 1);
+    assert_eq!(faces[0].family, "Inter");
+    assert_eq!(faces[0].weight, (100.0, 900.0));
+    assert_eq!(
+      faces[0].sources,
+      vec![
+        FontSource::Url {
+          url: "/themes/fonts/inter.woff2".to_owned(),
+          format: Some("woff2".to_owned())
+        },
+        FontSource::Local("Inter".to_owned()),
+      ]
+    );
+
+    let vbox = rules.compute(&Element::named("vbox"));
+    assert_eq!(vbox.font_family, vec![FontFamily::Named("Inter".to_owned()), FontFamily::SystemUi]);
+    assert_eq!(vbox.font_size, Some(16.0));
+    assert_eq!(vbox.font_weight, 700.0);
+    assert_eq!(vbox.font_style, FontStyle::Italic);
+    assert_eq!(vbox.line_height, LineHeight::Number(1.5));
+    assert_eq!(vbox.letter_spacing, Length::Px(8.0));
+
+    let label = rules.compute_with_parent(&Element::named("label"), &vbox);
+    assert_eq!(label.font_family, vbox.font_family);
+    assert_eq!(label.font_style, FontStyle::Italic);
+    assert_eq!(label.font_weight, 900.0);
+    assert_eq!(label.line_height, LineHeight::Length(Length::Px(24.0)));
+  }
+
+  #[test]
+  fn text_styles() {
+    let source = r#"
+    toolbar {
+      text-transform: capitalize;
+      white-space: nowrap;
+      text-wrap: balance;
+      text-decoration: underline dotted red;
+    }
+    label {
+      text-overflow: ellipsis;
+      text-indent: 2em;
+    }
+    label.reset {
+      text-wrap: initial;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let toolbar = rules.compute(&Element::named("toolbar"));
+    assert!(toolbar.text_decoration.underline);
+    assert!(!toolbar.text_decoration.line_through);
+    assert_eq!(toolbar.text_decoration.color, Some(RED_COLOR));
+    assert!(!toolbar.text_wraps());
+
+    let label = rules.compute_with_parent(&Element::named("label"), &toolbar);
+    assert_eq!(label.text_transform, TextTransform::Capitalize);
+    assert_eq!(label.text_transform.transform("open recent file"), "Open Recent File");
+    assert_eq!(label.text_overflow, TextOverflow::Ellipsis);
+    assert_eq!(label.text_indent.length, Length::Px(32.0));
+    assert!(!label.text_wraps());
+    assert_eq!(label.text_wrap, TextWrap::Balance);
+    // Text decorations are not inherited.
+    assert!(!label.text_decoration.underline);
+
+    let reset = rules.compute_with_parent(&Element::named("label").class("reset"), &toolbar);
+    assert_eq!(reset.text_wrap, TextWrap::Wrap);
+  }
+
+  #[test]
+  fn shadows() {
+    let source = r#"
+    button {
+      box-shadow: 0 2px 4px rgba(0, 0, 0, 0.5), inset 0 0 0 1px red;
+    }
+    text {
+      text-shadow: 1px 1px currentColor;
+    }
+    text.relative {
+      text-shadow: 1vw 1vw red, 2px 2px blue;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let button = rules.compute(&Element::named("button"));
+    assert_eq!(button.box_shadow.len(), 2);
+    assert_eq!(
+      button.box_shadow[0],
+      Shadow {
+        x: 0.0,
+        y: 2.0,
+        blur: 4.0,
+        spread: 0.0,
+        color: Some(Color {
+          r: 0.0,
+          g: 0.0,
+          b: 0.0,
+          a: 128.0 / 255.0,
+        }),
+        inset: false,
+      }
+    );
+    assert!(button.box_shadow[1].inset);
+    assert_eq!(button.box_shadow[1].spread, 1.0);
+    assert_eq!(button.box_shadow[1].color, Some(RED_COLOR));
+
+    let text = rules.compute_with_parent(&Element::named("text"), &button);
+    assert_eq!(text.box_shadow, vec![]);
+    assert_eq!(text.text_shadow[0].color, None);
+    assert_eq!(text.text_shadow[0].x, 1.0);
+
+    // Without a viewport, only the shadow relative to it is dropped.
+    let relative = rules.compute(&Element::named("text").class("relative"));
+    assert_eq!(relative.text_shadow.len(), 1);
+    assert_eq!(relative.text_shadow[0].x, 2.0);
+  }
+
+  #[test]
+  fn backgrounds() {
+    let source = r#"
+    toolbar {
+      background: url(images/noise.png) center / 16px 16px, linear-gradient(to bottom, white, red 80%) no-repeat green;
+    }
+    toolbar.badge {
+      background: url(images/badge.png) right 4px bottom 10% no-repeat;
+    }
+    "#;
+
+    let rules = parse(source, Some(std::path::Path::new("/themes/app.css"))).unwrap();
+    let toolbar = rules.compute(&Element::named("toolbar"));
+    assert_eq!(toolbar.background_color, GREEN_COLOR);
+    let layers = toolbar.background_layers();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].image, Image::Url("/themes/images/noise.png".to_owned()));
+    assert_eq!(
+      layers[0].position,
+      Position {
+        x: Length::Percent(50.0),
+        y: Length::Percent(50.0)
+      }
+    );
+    assert_eq!(layers[0].size, BackgroundSize::Explicit(Length::Px(16.0), Length::Px(16.0)));
+    assert_eq!(layers[1].repeat, (Repeat::NoRepeat, Repeat::NoRepeat));
+    match &layers[1].image {
+      Image::Gradient(g) => {
+        assert_eq!(g.shape, GradientShape::Linear(180.0));
+        assert_eq!(g.stops.len(), 2);
+        assert_eq!(g.stops[0].position, None);
+        assert_eq!(g.stops[1].color, RED_COLOR);
+        assert_eq!(g.stops[1].position, Some(Length::Percent(80.0)));
+      },
+      image => panic!("Unexpected image: {image:?}"),
+    }
+
+    // Offsets from the right and the bottom.
+    let badge = rules.compute(&Element::named("toolbar").class("badge"));
+    assert_eq!(
+      badge.background_layers()[0].position,
+      Position {
+        x: Length::Calc(CalcLength {
+          px: -4.0,
+          percent: 100.0,
+          ..CalcLength::default()
+        }),
+        y: Length::Percent(90.0)
+      }
+    );
+  }
+
+  #[test]
+  fn borders() {
+    let source = r#"
+    button {
+      border: thin dashed red;
+      border-left: none;
+      border-radius: 50%;
+      outline: 2px solid green;
+      outline-offset: 1px;
+    }
+    toolbar {
+      border-style: solid dotted;
+    }
+    button.flat {
+      outline-offset: initial;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let button = rules.compute(&Element::named("button"));
+    assert_eq!(button.border_style.top, BorderStyle::Dashed);
+    assert_eq!(button.border_style.left, BorderStyle::None);
+    assert_eq!(button.border.top.width, 1.0);
+    assert_eq!(button.border.top.color, RED_COLOR);
+    // `border-left: none` sets a medium width, but no border is drawn.
+    assert_eq!(button.border.left.width, 3.0);
+    assert_eq!(button.border_widths().left, 0.0);
+    assert_eq!(button.border_radius.nw, (Length::Percent(50.0), Length::Percent(50.0)));
+    assert!(button.outline.is_visible());
+    assert_eq!(button.outline.width, 2.0);
+    assert_eq!(button.outline.style, Some(BorderStyle::Solid));
+    assert_eq!(button.outline.color, Some(GREEN_COLOR));
+    assert_eq!(button.outline.offset, 1.0);
+
+    let toolbar = rules.compute(&Element::named("toolbar"));
+    assert_eq!(toolbar.border_style.bottom, BorderStyle::Solid);
+    assert_eq!(toolbar.border_style.right, BorderStyle::Dotted);
+    assert!(!toolbar.outline.is_visible());
+
+    let flat = rules.compute(&Element::named("button").class("flat"));
+    assert_eq!(flat.outline.offset, 0.0);
+  }
+
+  #[test]
+  fn visibility_and_cursor() {
+    let source = r#"
+    toolbar {
+      opacity: 50%;
+      visibility: hidden;
+      cursor: url(cursors/hand.png) 4 2, pointer;
+      pointer-events: none;
+    }
+    button {
+      visibility: visible;
+    }
+    .collapsed {
+      display: none;
+    }
+    "#;
+
+    let rules = parse(source, Some(std::path::Path::new("/themes/app.css"))).unwrap();
+    let toolbar = rules.compute(&Element::named("toolbar"));
+    assert_eq!(toolbar.opacity, 0.5);
+    assert_eq!(toolbar.visibility, Visibility::Hidden);
+    assert!(!toolbar.is_visible());
+    assert_eq!(toolbar.cursor.keyword, CursorKeyword::Pointer);
+    assert_eq!(toolbar.cursor.images[0].url, "/themes/cursors/hand.png");
+    assert_eq!(toolbar.cursor.images[0].hotspot, Some((4.0, 2.0)));
+
+    // Visibility, cursor and pointer-events are inherited, opacity is not.
+    let button = rules.compute_with_parent(&Element::named("button"), &toolbar);
+    assert_eq!(button.opacity, 1.0);
+    assert!(button.is_visible());
+    assert_eq!(button.cursor.keyword, CursorKeyword::Pointer);
+    assert_eq!(button.pointer_events, PointerEvents::None);
+    assert!(!button.is_interactive());
+
+    let collapsed = rules.compute(&Element::named("button").class("collapsed"));
+    assert_eq!(collapsed.display, Display::None);
+    assert!(!collapsed.is_visible());
+  }
+
+  #[test]
+  fn transitions() {
+    let source = r#"
+    button {
+      background-color: rgb(0, 0, 0);
+      border: 2px solid red;
+      padding: 10px;
+      transition: background-color 1s linear, border-color 2s steps(2) 1s;
+    }
+    button:hover {
+      background-color: rgb(200, 100, 0);
+      border-color: green;
+      padding: 20px;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let from = rules.compute(&Element::named("button"));
+    let to = rules.compute(&Element::named("button").pseudo_class(PseudoClass::Hover));
+    let transitions = to.transitions();
+    assert_eq!(transitions.len(), 2);
+    assert_eq!(transitions[0].property, TransitionProperty::Property("background-color".to_owned()));
+    let border = TransitionProperty::Property("border-color".to_owned());
+    assert!(border.covers("border-top-color"));
+    assert!(!border.covers("border-top-width"));
+    assert!(TransitionProperty::Property("gap".to_owned()).covers("row-gap"));
+    assert!(!TransitionProperty::Property("border-top".to_owned()).covers("border-top-left-radius"));
+    assert_eq!(transitions[0].timing_function, TimingFunction::Linear);
+    assert_eq!(transitions[1].delay, 1.0);
+    assert_eq!(TimingFunction::default().progress(0.0), 0.0);
+    assert!((TimingFunction::default().progress(0.5) - 0.8).abs() < 0.01);
+
+    let transition = Transition::new(from, to.clone());
+    assert_eq!(transition.duration(), 3.0);
+    let half_way = transition.at(0.5);
+    assert_eq!(half_way.background_color, Color::from_rgb8(100, 50, 0));
+    assert_eq!(half_way.border.top.color, RED_COLOR);
+    assert_eq!(
+      transition.at(2.0).border.left.color,
+      Color {
+        r: 0.5,
+        g: 64.0 / 255.0,
+        b: 0.0,
+        a: 1.0
+      }
+    );
+    // Properties without a transition change immediately.
+    assert_eq!(half_way.padding.top, Length::Px(20.0));
+    assert!(transition.is_finished(3.0));
+    assert_eq!(transition.at(3.0), to);
+  }
+
+  #[test]
+  fn animations() {
+    let source = r#"
+    @keyframes pulse {
+      from, to { opacity: 1 }
+      50% { opacity: 0.5; animation-timing-function: steps(1) }
+    }
+    @keyframes grow {
+      to { padding: 20px; text-align: center }
+    }
+    button {
+      padding: 10px;
+      animation: pulse 2s linear 3 alternate, grow 1s linear 1s forwards;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let button = rules.compute(&Element::named("button"));
+    let animations = button.animations();
+    assert_eq!(animations.len(), 2);
+    assert_eq!(animations[0].iteration_count, IterationCount::Count(3.0));
+    assert_eq!(animations[0].direction, AnimationDirection::Alternate);
+    assert_eq!(animations[1].fill_mode, FillMode::Forwards);
+
+    let timeline = rules.timeline(&button, None);
+    assert_eq!(timeline.at(0.5).opacity, 0.75);
+    // The 50% keyframe holds its value with `steps(1)`.
+    assert_eq!(timeline.at(1.5).opacity, 0.5);
+    // The second iteration plays backwards.
+    assert_eq!(animations[0].progress(2.5), Some(0.75));
+    // Before its delay, `grow` doesn't apply.
+    assert_eq!(timeline.at(0.5).padding.top, Length::Px(10.0));
+    assert_eq!(timeline.at(1.5).padding.top, Length::Px(15.0));
+    // After the end, `forwards` keeps the last keyframe.
+    assert_eq!(timeline.at(10.0).padding.top, Length::Px(20.0));
+    assert_eq!(timeline.at(10.0).opacity, 1.0);
+    // Properties that can't be interpolated change at the keyframe.
+    assert_eq!(timeline.at(1.5).text_align, Align::Start);
+    assert_eq!(timeline.at(10.0).text_align, Align::Center);
+    assert!(!timeline.is_finished(5.0));
+    assert!(timeline.is_finished(6.0));
+  }
+
+  #[test]
+  fn current_and_system_colors() {
+    let source = r#"
+    toolbar {
+      border: 1px solid currentColor;
+      outline: 1px solid;
+      color: green;
+    }
+    button {
+      color: currentColor;
+      background-color: Canvas;
+      border-color: ButtonBorder;
+    }
+    label {
+      color: red;
+    }
+    "#;
+
+    set_theme(SystemTheme::Dark);
+
+    let rules = parse(source, None).unwrap();
+    let toolbar = rules.compute(&Element::named("toolbar"));
+    assert_eq!(toolbar.border.top.color, GREEN_COLOR);
+    assert_eq!(toolbar.outline.color, None);
+    assert_eq!(toolbar.current_color(toolbar.outline.color), GREEN_COLOR);
+
+    // `color: currentColor` is the inherited color.
+    let button = rules.compute_with_parent(&Element::named("button"), &toolbar);
+    assert_eq!(button.color, GREEN_COLOR);
+    assert_eq!(button.background_color, Color::from_rgb8(0x1e, 0x1e, 0x1e));
+    assert_eq!(button.border.top.color, Color::from_rgb8(0x85, 0x85, 0x85));
+
+    let label = rules.compute(&Element::named("label"));
+    assert_eq!(label.current_color(None), RED_COLOR);
+  }
+
+  #[test]
+  fn modern_colors() {
+    let source = r#"
+    :root {
+      --accent: #3366cc;
+    }
+    button {
+      color: color-mix(in srgb, red, blue);
+      background-color: rgb(from var(--accent) r g b / 50%);
+      border-color: light-dark(white, black);
+      outline-color: color(display-p3 0 1 0);
+    }
+    label {
+      color: color-mix(in oklch, #3366cc 40%, white);
+    }
+    "#;
+
+    set_theme(SystemTheme::Dark);
+
+    let close = |a: Color, b: Color| [a.r - b.r, a.g - b.g, a.b - b.b, a.a - b.a].iter().all(|d| d.abs() < 0.01);
+    let rules = parse(source, None).unwrap();
+    let root = rules.compute(&Element::root());
+    let button = rules.compute_with_parent(&Element::named("button"), &root);
+    assert!(close(
+      button.color,
+      Color {
+        r: 0.5,
+        g: 0.0,
+        b: 0.5,
+        a: 1.0
+      }
+    ));
+    assert!(close(
+      button.background_color,
+      Color {
+        a: 0.5,
+        ..Color::from_rgb8(51, 102, 204)
+      }
+    ));
+    assert_eq!(button.border.top.color, Color::from_rgb8(0, 0, 0));
+    // Colors out of the sRGB gamut are not clamped.
+    let p3 = button.outline.color.unwrap();
+    assert!(p3.g > 1.0 && p3.r < 0.0);
+
+    let label = rules.compute(&Element::named("label"));
+    assert!(label.color.r > 0.2 && label.color.r < 1.0 && label.color.b > label.color.r);
+  }
+
+  #[test]
+  fn system_accent() {
+    let source = r#"
+    button {
+      background-color: var(--system-accent);
+      color: AccentColorText;
+      border-color: AccentColor;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let root = rules.compute(&Element::root());
+    // System variables are not part of the computed variables.
+    assert!(root.variables.is_empty());
+    let button = rules.compute_with_parent(&Element::named("button"), &root);
+    assert_eq!(button.background_color, Color::from_rgb8(0x00, 0x75, 0xff));
+    assert_eq!(button.border.top.color, button.background_color);
+    assert_eq!(button.color, Color::from_rgb8(0xff, 0xff, 0xff));
+    let orphan = rules.compute(&Element::named("button"));
+    assert_eq!(orphan.background_color, button.background_color);
+  }
+
+  #[test]
+  fn logical_pseudo_classes() {
+    let source = r#"
+    button:where(.big) {
+      padding: 10px;
+    }
+    button {
+      padding: 20px;
+    }
+    button:is(.big, .huge) {
+      margin: 4px;
+    }
+    button:not(.disabled) {
+      color: green;
+    }
+    vbox:has(> input:focus) {
+      color: red;
+    }
+    vbox:has(label .icon) {
+      padding: 8px;
+    }
+    vbox:has(+ label, > label) {
+      margin: 2px;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let big = rules.compute(&Element::named("button").class("big"));
+    // `:where()` has no specificity.
+    assert_eq!(big.padding.top, Length::Px(20.0));
+    assert_eq!(big.margin.top, Length::Px(4.0));
+    assert_eq!(big.color, GREEN_COLOR);
+    let huge = rules.compute(&Element::named("button").class("huge").class("disabled"));
+    assert_eq!(huge.margin.top, Length::Px(4.0));
+    assert_eq!(huge.color, Color::default());
+
+    let vbox = Element::named("vbox").child(Element::named("input"));
+    assert_eq!(rules.compute(&vbox).color, Color::default());
+    let focused = Element::named("vbox").child(Element::named("input").pseudo_class(PseudoClass::Focus));
+    assert_eq!(rules.compute(&focused).color, RED_COLOR);
+    // Only children match `>`.
+    let nested = Element::named("vbox").child(Element::named("hbox").child(Element::named("input").pseudo_class(PseudoClass::Focus)));
+    assert_eq!(rules.compute(&nested).color, Color::default());
+    let icon = Element::named("vbox").child(Element::named("label").child(Element::named("image").class("icon")));
+    assert_eq!(rules.compute(&icon).padding.top, Length::Px(8.0));
+    let no_label = Element::named("vbox").child(Element::named("hbox").child(Element::named("image").class("icon")));
+    assert_eq!(rules.compute(&no_label).padding.top, Length::Px(0.0));
+    // Selectors with sibling combinators in `:has()` are dropped.
+    assert_eq!(rules.compute(&icon).margin.top, Length::Px(0.0));
+  }
+
+  #[derive(Clone, Copy)]
+  enum WidgetState {
+    Selected,
+    Expanded,
+  }
+
+  impl CustomPseudoClass for WidgetState {
+    const ALL: &'static [Self] = &[WidgetState::Selected, WidgetState::Expanded];
+
+    fn name(self) -> &'static str {
+      match self {
+        WidgetState::Selected => "selected",
+        WidgetState::Expanded => "expanded",
+      }
+    }
+  }
+
+  #[test]
+  fn custom_pseudo_classes() {
+    let source = r#"
+    row:selected {
+      color: red;
+    }
+    row:selected:expanded {
+      padding: 4px;
+    }
+    "#;
+
+    register_pseudo_classes::<WidgetState>();
+    assert!(is_registered_pseudo_class("expanded"));
+    assert!(!is_registered_pseudo_class("dragging"));
+
+    let rules = parse(source, None).unwrap();
+    assert_eq!(rules.compute(&Element::named("row")).color, Color::default());
+    let selected = rules.compute(&Element::named("row").state(WidgetState::Selected));
+    assert_eq!(selected.color, RED_COLOR);
+    assert_eq!(selected.padding.top, Length::Px(0.0));
+    let expanded = rules.compute(&Element::named("row").state(WidgetState::Selected).state(WidgetState::Expanded));
+    assert_eq!(expanded.padding.top, Length::Px(4.0));
+    assert!(rules.warnings().is_empty());
+
+    let rules = parse("row:dragging { color: red; }", None).unwrap();
+    assert_eq!(rules.warnings().len(), 1);
+    assert!(rules.warnings()[0].contains(":dragging"));
+    // Functional custom pseudo-classes are never matched, even registered.
+    let rules = parse("row:state(selected), row:expanded(true) { color: red; }", None).unwrap();
+    let warnings = rules.warnings();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains(":state()"));
+    assert!(warnings[1].contains(":expanded()"));
+  }
+
+  #[derive(Clone, Copy)]
+  enum SliderPart {
+    Thumb,
+    Track,
+  }
+
+  impl CustomPseudoElement for SliderPart {
+    const ALL: &'static [Self] = &[SliderPart::Thumb, SliderPart::Track];
+
+    fn name(self) -> &'static str {
+      match self {
+        SliderPart::Thumb => "thumb",
+        SliderPart::Track => "track",
+      }
+    }
+  }
+
+  #[test]
+  fn widget_parts() {
+    let source = r#"
+    slider::thumb {
+      color: red;
+    }
+    slider.large::track {
+      padding: 4px;
+    }
+    tabbar::part(close-button) {
+      color: green;
+    }
+    "#;
+
+    register_pseudo_elements::<SliderPart>();
+    assert!(is_registered_pseudo_element("thumb"));
+
+    let rules = parse(source, None).unwrap();
+    let thumb = rules.compute(&Element::custom_pseudo(SliderPart::Thumb, Element::named("slider")));
+    assert_eq!(thumb.color, RED_COLOR);
+    let thumb = rules.compute(&Element::custom_pseudo(SliderPart::Thumb, Element::named("scrollarea")));
+    assert_eq!(thumb.color, Color::default());
+    let track = rules.compute(&Element::custom_pseudo(SliderPart::Track, Element::named("slider")));
+    assert_eq!(track.padding.top, Length::Px(0.0));
+    let track = rules.compute(&Element::custom_pseudo(SliderPart::Track, Element::named("slider").class("large")));
+    assert_eq!(track.padding.top, Length::Px(4.0));
+
+    let close = rules.compute(&Element::part("close-button", Element::named("tabbar")));
+    assert_eq!(close.color, GREEN_COLOR);
+    let other = rules.compute(&Element::part("add-button", Element::named("tabbar")));
+    assert_eq!(other.color, Color::default());
+  }
+
+  #[test]
+  fn pseudo_elements() {
+    let source = r#"
+    button {
+      color: green;
+      font-size: 20px;
+    }
+    button.primary:hover::before {
+      color: red;
+      padding: 2px;
+    }
+    ::after {
+      margin: 1px;
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let button = || Element::named("button").class("primary");
+    let properties = rules.compute(&button());
+    // Pseudo-elements inherit from their originating element.
+    let before = rules.compute_with_parent(&Element::before(button()), &properties);
+    assert_eq!(before.color, GREEN_COLOR);
+    assert_eq!(before.font_size, Some(20.0));
+    assert_eq!(before.padding.top, Length::Px(0.0));
+
+    let hovered = button().pseudo_class(PseudoClass::Hover);
+    let properties = rules.compute(&hovered);
+    let before = rules.compute_with_parent(&Element::before(hovered), &properties);
+    assert_eq!(before.color, RED_COLOR);
+    assert_eq!(before.padding.top, Length::Px(2.0));
+    // The pseudo-element itself doesn't match the selector of its originating
+    // element.
+    assert_eq!(properties.padding.top, Length::Px(0.0));
+
+    let after = rules.compute(&Element::after(Element::named("label")));
+    assert_eq!(after.margin.top, Length::Px(1.0));
+    assert_eq!(rules.compute(&Element::named("label")).margin.top, Length::Px(0.0));
+  }
+
+  #[test]
+  fn generated_content() {
+    let source = r#"
+    button::before {
+      content: "(" attr(data-count) ") " url(icons/star.svg) counter(item, upper-roman);
+    }
+    label[required]::after {
+      content: " " attr(data-required, "(required)") / "";
+    }
+    label::before {
+      content: none;
+    }
+    hbox::before {
+      --mark: "*";
+      content: var(--mark) " ";
+    }
+    hbox.broken::before {
+      content: var(--missing);
+    }
+    "#;
+
+    let rules = parse(source, None).unwrap();
+    let button = rules.compute(&Element::before(Element::named("button").attribute("data-count", "3")));
+    assert_eq!(
+      button.content,
+      GeneratedContent::Items(vec![
+        ContentItem::Text("(".to_owned()),
+        ContentItem::Text("3".to_owned()),
+        ContentItem::Text(") ".to_owned()),
+        ContentItem::Url("icons/star.svg".to_owned()),
+        ContentItem::Counter {
+          name: "item".to_owned(),
+          style: Some("upper-roman".to_owned()),
+        },
+      ])
+    );
+    assert_eq!(button.content.text(), "(3) ");
+
+    let required = rules.compute(&Element::after(Element::named("label").attribute("required", "")));
+    assert!(required.content.is_generated());
+    assert_eq!(required.content.text(), " (required)");
+    let before = rules.compute(&Element::before(Element::named("label")));
+    assert_eq!(before.content, GeneratedContent::None);
+    assert!(!before.content.is_generated());
+    assert_eq!(rules.compute(&Element::named("button")).content, GeneratedContent::Normal);
+
+    let hbox = rules.compute(&Element::before(Element::named("hbox")));
+    assert_eq!(hbox.content.text(), "* ");
+    // Invalid at computed-value time.
+    let broken = rules.compute(&Element::before(Element::named("hbox").class("broken")));
+    assert_eq!(broken.content, GeneratedContent::Normal);
+  }
 }
@@ -7,10 +7,11 @@ use lightningcss::stylesheet::{ParserOptions, StyleSheet};
 use log::debug;
 use ouroboros::self_referencing;
 
+use crate::animation::Timeline;
 use crate::compute::{pre_compute, PreComputedRules};
 use crate::element::Element;
 use crate::file_watcher::{watch as watch_file, Event as file_event};
-use crate::properties::ComputedProperties;
+use crate::properties::{ComputedProperties, Context, FontFace};
 use crate::themes::{watch as watch_theme, Event as theme_event};
 
 /// Events sent from CSS thread.
@@ -37,7 +38,43 @@ pub struct Rules(OwnedResult);
 impl Rules {
   /// Compute properties of element.
   pub fn compute(&self, element: &Element<'_>) -> ComputedProperties {
-    self.0.with_rules(|s| s.compute(element))
+    self.0.with_rules(|s| s.compute(element, None, &Context::default()))
+  }
+
+  /// Compute properties of element, inheriting from the computed properties
+  /// of its parent. Inherited properties (`color`, the `font-*` properties,
+  /// `text-align`…) and custom properties flow from the parent.
+  pub fn compute_with_parent(&self, element: &Element<'_>, parent: &ComputedProperties) -> ComputedProperties {
+    self.0.with_rules(|s| s.compute(element, Some(parent), &Context::default()))
+  }
+
+  /// Compute properties of element, resolving relative lengths (`em`,
+  /// `rem`, `vw`, percentages, `calc()`…) against `context`.
+  pub fn compute_in_context(&self, element: &Element<'_>, parent: Option<&ComputedProperties>, context: &Context) -> ComputedProperties {
+    self.0.with_rules(|s| s.compute(element, parent, context))
+  }
+
+  /// Animations of an element whose computed properties are `properties`,
+  /// with their `@keyframes`.
+  pub fn timeline(&self, properties: &ComputedProperties, parent: Option<&ComputedProperties>) -> Timeline {
+    self.0.with_rules(|s| s.timeline(properties, parent, &Context::default()))
+  }
+
+  /// Animations of an element, with the lengths of their keyframes resolved
+  /// against `context`, like [`Rules::compute_in_context`].
+  pub fn timeline_in_context(&self, properties: &ComputedProperties, parent: Option<&ComputedProperties>, context: &Context) -> Timeline {
+    self.0.with_rules(|s| s.timeline(properties, parent, context))
+  }
+
+  /// Fonts declared with `@font-face`, for the toolkit to load.
+  pub fn font_faces(&self) -> Vec<FontFace> {
+    self.0.with_rules(|s| s.font_faces.clone())
+  }
+
+  /// Problems found in the stylesheet that don't prevent it from being
+  /// used, like pseudo-classes that were not registered and never match.
+  pub fn warnings(&self) -> Vec<String> {
+    self.0.with_rules(|s| s.warnings.clone())
   }
 }
 
@@ -70,7 +107,7 @@ pub fn parse_string_sync(source: impl Into<String>, path: Option<&Path>) -> Resu
         },
       };
       let stylesheet = StyleSheet::parse(source, options).map_err(|e| anyhow!("Parsing error: {e}"))?;
-      Ok(pre_compute(stylesheet))
+      Ok(pre_compute(stylesheet, path.and_then(Path::parent)))
     },
   }
   .try_build()
@@ -1,28 +1,74 @@
 #![allow(missing_docs)]
 
-use anyhow::{bail, Result};
-use lightningcss::cssparser::RGBA;
-use lightningcss::printer::PrinterOptions;
-use lightningcss::properties::Property;
-use lightningcss::values::color::CssColor;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
 
-/// RGBA color.
+use anyhow::{anyhow, bail, Result};
+use lightningcss::printer::{Printer, PrinterOptions};
+use lightningcss::properties::align::{
+  AlignContent, AlignItems, AlignSelf, BaselinePosition, ContentDistribution, ContentPosition, JustifyContent, JustifyItems, JustifySelf, LegacyJustify,
+  SelfPosition,
+};
+use lightningcss::properties::background::BackgroundRepeatKeyword;
+use lightningcss::properties::border::LineStyle;
+use lightningcss::properties::custom::{CustomProperty, Token, TokenList, TokenOrValue};
+use lightningcss::properties::display::{Display as CssDisplay, DisplayInside, DisplayKeyword, DisplayPair};
+use lightningcss::properties::flex::{FlexDirection, FlexWrap};
+use lightningcss::properties::font::{
+  AbsoluteFontWeight, FontFamily as CssFontFamily, FontStretch as CssFontStretch, FontWeight as CssFontWeight, GenericFontFamily,
+};
+use lightningcss::properties::grid::{GridAutoFlow, GridLine as CssGridLine};
+use lightningcss::properties::text::{TextDecorationLine, TextDecorationStyle};
+use lightningcss::properties::ui::{Cursor as CssCursor, CursorKeyword as CssCursorKeyword};
+use lightningcss::properties::{Property, PropertyId};
+use lightningcss::rules::font_face::{FontFaceProperty, FontFaceRule, FontFormat, FontStyle as FontFaceStyle, Source, UrlSource};
+use lightningcss::traits::{IntoOwned, ToCss};
+use lightningcss::values::color::{CssColor, SRGB};
+use lightningcss::values::easing::{EasingFunction, StepPosition as CssStepPosition};
+use lightningcss::values::gradient::{LineDirection, ShapeExtent as CssShapeExtent};
+use lightningcss::values::length::LengthPercentage;
+use lightningcss::values::percentage::Percentage;
+use lightningcss::values::position::{HorizontalPositionKeyword, PositionComponent, VerticalPositionKeyword};
+use lightningcss::values::size::Size2D;
+use lightningcss::values::syntax::ParsedComponent;
+use log::warn;
+
+use crate::themes::{SystemColors, SystemTheme};
+use crate::units::Lengths;
+
+/// RGBA color, with components from 0 to 1. Colors outside of sRGB, like
+/// `display-p3` or `oklch` ones, have components out of this range.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color {
-  pub r: u8,
-  pub g: u8,
-  pub b: u8,
-  pub a: u8,
+  pub r: f32,
+  pub g: f32,
+  pub b: f32,
+  pub a: f32,
 }
 
 impl Color {
   pub(crate) fn transparent() -> Color {
-    Color { r: 0, g: 0, b: 0, a: 0 }
+    Color {
+      r: 0.0,
+      g: 0.0,
+      b: 0.0,
+      a: 0.0,
+    }
+  }
+
+  pub(crate) fn from_rgb8(r: u8, g: u8, b: u8) -> Color {
+    Color {
+      r: r as f32 / 255.0,
+      g: g as f32 / 255.0,
+      b: b as f32 / 255.0,
+      a: 1.0,
+    }
   }
 
   #[cfg(feature = "toolkit-iced")]
   pub(crate) fn to_opt(self) -> Option<Color> {
-    if self.a == 0 {
+    if self.a == 0.0 {
       None
     } else {
       Some(self)
@@ -30,112 +76,1611 @@ impl Color {
   }
 }
 
-impl From<RGBA> for Color {
-  fn from(c: RGBA) -> Color {
-    Color {
-      r: c.red,
-      g: c.green,
-      b: c.blue,
-      a: c.alpha,
+/// Missing (`none`) components are zero.
+impl From<SRGB> for Color {
+  fn from(c: SRGB) -> Color {
+    let zero = |v: f32| if v.is_nan() { 0.0 } else { v };
+    Color {
+      r: zero(c.r),
+      g: zero(c.g),
+      b: zero(c.b),
+      a: zero(c.alpha),
+    }
+  }
+}
+
+impl Default for Color {
+  fn default() -> Self {
+    Color::transparent()
+  }
+}
+
+/// Colors that depend on the element or the theme, like `currentColor`, are
+/// transparent. See `resolve_color`.
+impl From<&CssColor> for Color {
+  fn from(c: &CssColor) -> Self {
+    SRGB::try_from(c).map_or_else(|_| Color::transparent(), Color::from)
+  }
+}
+
+/// Layout direction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+  #[default]
+  Horizontal,
+  Vertical,
+  HorizontalReverse,
+  VerticalReverse,
+}
+
+impl Direction {
+  pub fn is_horizontal(self) -> bool {
+    matches!(self, Direction::Horizontal | Direction::HorizontalReverse)
+  }
+
+  pub fn is_reverse(self) -> bool {
+    matches!(self, Direction::HorizontalReverse | Direction::VerticalReverse)
+  }
+}
+
+/// Wrapping of flex items.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Wrap {
+  #[default]
+  NoWrap,
+  Wrap,
+  WrapReverse,
+}
+
+/// Distribution of the space between and around items (`justify-content`,
+/// `align-content`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ContentAlign {
+  #[default]
+  Normal,
+  Start,
+  End,
+  /// Start of the flex direction, which is the end for reverse directions.
+  FlexStart,
+  FlexEnd,
+  Center,
+  SpaceBetween,
+  SpaceAround,
+  SpaceEvenly,
+  Stretch,
+}
+
+/// Alignment of items in their container (`align-items`, `align-self`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ItemAlign {
+  #[default]
+  Normal,
+  Start,
+  End,
+  /// Start of the flex direction, which is the end for reverse directions.
+  FlexStart,
+  FlexEnd,
+  Center,
+  Stretch,
+  Baseline,
+  LastBaseline,
+}
+
+/// Four values for each corner of an element.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Corners<T>
+where T: Default
+{
+  pub nw: (T, T),
+  pub ne: (T, T),
+  pub sw: (T, T),
+  pub se: (T, T),
+}
+
+/// Width and color definition.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WidthAndColor {
+  pub width: f32,
+  pub color: Color,
+}
+
+/// Style of a border or an outline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BorderStyle {
+  #[default]
+  None,
+  Hidden,
+  Dotted,
+  Dashed,
+  Solid,
+  Double,
+  Groove,
+  Ridge,
+  Inset,
+  Outset,
+}
+
+impl BorderStyle {
+  /// Whether a line is drawn. If not, the width is zero.
+  pub fn is_visible(self) -> bool {
+    !matches!(self, BorderStyle::None | BorderStyle::Hidden)
+  }
+}
+
+impl From<&LineStyle> for BorderStyle {
+  fn from(s: &LineStyle) -> Self {
+    match s {
+      LineStyle::None => BorderStyle::None,
+      LineStyle::Hidden => BorderStyle::Hidden,
+      LineStyle::Dotted => BorderStyle::Dotted,
+      LineStyle::Dashed => BorderStyle::Dashed,
+      LineStyle::Solid => BorderStyle::Solid,
+      LineStyle::Double => BorderStyle::Double,
+      LineStyle::Groove => BorderStyle::Groove,
+      LineStyle::Ridge => BorderStyle::Ridge,
+      LineStyle::Inset => BorderStyle::Inset,
+      LineStyle::Outset => BorderStyle::Outset,
+    }
+  }
+}
+
+/// Line drawn outside of the border, without taking any space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outline {
+  pub width: f32,
+  /// `None` for `auto`: the platform's focus ring.
+  pub style: Option<BorderStyle>,
+  /// `None` for `currentColor`.
+  pub color: Option<Color>,
+  /// Distance from the border.
+  pub offset: f32,
+}
+
+impl Default for Outline {
+  fn default() -> Self {
+    Outline {
+      width: MEDIUM_WIDTH,
+      style: Some(BorderStyle::None),
+      color: None,
+      offset: 0.0,
+    }
+  }
+}
+
+impl Outline {
+  /// Whether the outline is drawn.
+  pub fn is_visible(&self) -> bool {
+    self.style.map_or(true, BorderStyle::is_visible) && self.width > 0.0
+  }
+}
+
+/// Width of `medium` borders and outlines.
+const MEDIUM_WIDTH: f32 = 3.0;
+
+/// Four values for each side of an element.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sides<T>
+where T: Default
+{
+  pub top: T,
+  pub right: T,
+  pub bottom: T,
+  pub left: T,
+}
+
+/// Length, as computed. Resolved to pixels when possible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+  Px(f32),
+  /// Percentage (`50%` is `Percent(50.0)`) of a size unknown to guicss,
+  /// usually the containing block.
+  Percent(f32),
+  Auto,
+  /// Font-relative length, when the font size is unknown.
+  Em(f32),
+  MinContent,
+  MaxContent,
+  FitContent,
+  /// Sum of lengths relative to sizes unknown to guicss, like
+  /// `calc(100% - 2em)` without a containing block and a font size.
+  Calc(CalcLength),
+}
+
+impl Default for Length {
+  fn default() -> Self {
+    Length::Px(0.0)
+  }
+}
+
+impl Length {
+  /// `Px`, `Em`, `Percent` and `Calc` lengths as a sum.
+  pub(crate) fn to_sum(self) -> Option<CalcLength> {
+    let zero = CalcLength::default();
+    match self {
+      Length::Px(px) => Some(CalcLength::px(px)),
+      Length::Em(em) => Some(CalcLength { em, ..zero }),
+      Length::Percent(percent) => Some(CalcLength { percent, ..zero }),
+      Length::Calc(sum) => Some(sum),
+      Length::Auto | Length::MinContent | Length::MaxContent | Length::FitContent => None,
+    }
+  }
+}
+
+/// Length made of parts relative to different sizes, that are unknown when
+/// the properties are computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CalcLength {
+  pub px: f32,
+  /// Multiple of the font size.
+  pub em: f32,
+  /// Percentage of a size unknown to guicss, usually the containing block.
+  pub percent: f32,
+  /// Percentages of the width, height, smaller and larger dimension of the
+  /// viewport.
+  pub vw: f32,
+  pub vh: f32,
+  pub vmin: f32,
+  pub vmax: f32,
+}
+
+impl CalcLength {
+  pub(crate) fn px(px: f32) -> CalcLength {
+    CalcLength { px, ..CalcLength::default() }
+  }
+
+  /// Length in pixels, with the font size, the size percentages are
+  /// relative to, and the width and height of the viewport.
+  pub fn to_px(&self, font_size: f32, basis: f32, (width, height): (f32, f32)) -> f32 {
+    self.px +
+      self.em * font_size +
+      (self.percent * basis + self.vw * width + self.vh * height + self.vmin * width.min(height) + self.vmax * width.max(height)) / 100.0
+  }
+
+  /// Pixels, if no part is relative to an unknown size.
+  pub(crate) fn to_known_px(self) -> Option<f32> {
+    (self == CalcLength::px(self.px)).then_some(self.px)
+  }
+
+  /// The simplest `Length` for this sum.
+  pub(crate) fn into_length(self) -> Length {
+    let zero = CalcLength::default();
+    match self {
+      CalcLength { px, .. } if self == CalcLength::px(px) => Length::Px(px),
+      CalcLength { em, .. } if self == CalcLength { em, ..zero } => Length::Em(em),
+      CalcLength { percent, .. } if self == CalcLength { percent, ..zero } => Length::Percent(percent),
+      sum => Length::Calc(sum),
+    }
+  }
+}
+
+impl std::ops::Add for CalcLength {
+  type Output = CalcLength;
+
+  fn add(self, other: CalcLength) -> CalcLength {
+    CalcLength {
+      px: self.px + other.px,
+      em: self.em + other.em,
+      percent: self.percent + other.percent,
+      vw: self.vw + other.vw,
+      vh: self.vh + other.vh,
+      vmin: self.vmin + other.vmin,
+      vmax: self.vmax + other.vmax,
+    }
+  }
+}
+
+impl std::ops::Mul<f32> for CalcLength {
+  type Output = CalcLength;
+
+  fn mul(self, n: f32) -> CalcLength {
+    CalcLength {
+      px: self.px * n,
+      em: self.em * n,
+      percent: self.percent * n,
+      vw: self.vw * n,
+      vh: self.vh * n,
+      vmin: self.vmin * n,
+      vmax: self.vmax * n,
+    }
+  }
+}
+
+/// Direction-agnostic alignment
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+  #[default]
+  Start,
+  Center,
+  End,
+  Justify,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecorationStyle {
+  #[default]
+  Solid,
+  Double,
+  Dotted,
+  Dashed,
+  Wavy,
+}
+
+/// Lines drawn over, under or through the text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextDecoration {
+  pub underline: bool,
+  pub overline: bool,
+  pub line_through: bool,
+  pub style: DecorationStyle,
+  /// `None` for `currentColor`.
+  pub color: Option<Color>,
+  /// `None` for `auto` and `from-font`.
+  pub thickness: Option<Length>,
+}
+
+impl TextDecoration {
+  fn set_line(&mut self, line: &TextDecorationLine) {
+    self.underline = line.contains(TextDecorationLine::Underline);
+    self.overline = line.contains(TextDecorationLine::Overline);
+    self.line_through = line.contains(TextDecorationLine::LineThrough);
+  }
+}
+
+impl From<&TextDecorationStyle> for DecorationStyle {
+  fn from(s: &TextDecorationStyle) -> Self {
+    match s {
+      TextDecorationStyle::Solid => DecorationStyle::Solid,
+      TextDecorationStyle::Double => DecorationStyle::Double,
+      TextDecorationStyle::Dotted => DecorationStyle::Dotted,
+      TextDecorationStyle::Dashed => DecorationStyle::Dashed,
+      TextDecorationStyle::Wavy => DecorationStyle::Wavy,
+    }
+  }
+}
+
+/// `currentColor` is `current`. System colors and `light-dark()` are those
+/// of the theme `colors` were read for.
+fn resolve_color(c: &CssColor, current: Color, colors: &SystemColors) -> Color {
+  match c {
+    CssColor::CurrentColor => current,
+    CssColor::System(s) => colors.get(*s),
+    CssColor::LightDark(light, dark) => {
+      match colors.theme {
+        SystemTheme::Light => resolve_color(light, current, colors),
+        SystemTheme::Dark => resolve_color(dark, current, colors),
+      }
+    },
+    c => c.into(),
+  }
+}
+
+/// Position of a background image, or center of a radial gradient.
+/// Percentages are kept as percentages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+  pub x: Length,
+  pub y: Length,
+}
+
+impl Default for Position {
+  fn default() -> Self {
+    Position {
+      x: Length::Percent(0.0),
+      y: Length::Percent(0.0),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeExtent {
+  ClosestSide,
+  FarthestSide,
+  ClosestCorner,
+  FarthestCorner,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RadialShape {
+  /// Radius in pixels.
+  Circle(f32),
+  Ellipse(Length, Length),
+  /// Sized to reach a side or a corner of the box.
+  Extent {
+    circle: bool,
+    extent: ShapeExtent,
+  },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape {
+  /// Angle in degrees, clockwise, `0.0` pointing to the top. Corners
+  /// (`to top right`) assume a square box.
+  Linear(f32),
+  Radial {
+    shape: RadialShape,
+    center: Position,
+  },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+  pub color: Color,
+  /// `None` when stops are evenly spread.
+  pub position: Option<Length>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+  pub shape: GradientShape,
+  pub stops: Vec<ColorStop>,
+  pub repeating: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Image {
+  /// `url()`, resolved against the directory of the stylesheet.
+  Url(String),
+  Gradient(Gradient),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackgroundSize {
+  /// Width and height, possibly `auto`.
+  Explicit(Length, Length),
+  Cover,
+  Contain,
+}
+
+impl Default for BackgroundSize {
+  fn default() -> Self {
+    BackgroundSize::Explicit(Length::Auto, Length::Auto)
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Repeat {
+  #[default]
+  Repeat,
+  Space,
+  Round,
+  NoRepeat,
+}
+
+/// Image of a background, and how it is laid out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackgroundLayer {
+  pub image: Image,
+  pub position: Position,
+  pub size: BackgroundSize,
+  /// Horizontal and vertical repetition.
+  pub repeat: (Repeat, Repeat),
+}
+
+/// Item `i` of a list of layer values, which repeats if it is shorter than
+/// the list of images.
+fn layer_value<T: Copy + Default>(list: &[T], i: usize) -> T {
+  match list.len() {
+    0 => T::default(),
+    len => list[i % len],
+  }
+}
+
+/// Position along one axis. `is_end` tells if a side keyword is `right` or
+/// `bottom`.
+fn position_component<S>(c: &PositionComponent<S>, is_end: impl Fn(&S) -> bool, length: &impl Fn(&LengthPercentage) -> Result<Length>) -> Result<Length> {
+  match c {
+    PositionComponent::Center => Ok(Length::Percent(50.0)),
+    PositionComponent::Length(l) => length(l),
+    PositionComponent::Side { side, offset } => {
+      let offset = offset.as_ref().map(length).transpose()?;
+      match (is_end(side), offset) {
+        (false, None) => Ok(Length::Percent(0.0)),
+        (true, None) => Ok(Length::Percent(100.0)),
+        (false, Some(offset)) => Ok(offset),
+        // `right 10px` is `calc(100% - 10px)`.
+        (true, Some(offset)) => {
+          let offset = offset.to_sum().ok_or_else(|| anyhow!("Invalid offset {offset:?}"))?;
+          let end = CalcLength {
+            percent: 100.0,
+            ..CalcLength::default()
+          };
+          Ok((end + offset * -1.0).into_length())
+        },
+      }
+    },
+  }
+}
+
+fn line_angle(d: &LineDirection) -> f32 {
+  use HorizontalPositionKeyword::{Left, Right};
+  use VerticalPositionKeyword::{Bottom, Top};
+  match d {
+    LineDirection::Angle(a) => a.to_degrees(),
+    LineDirection::Vertical(Top) => 0.0,
+    LineDirection::Horizontal(Right) => 90.0,
+    LineDirection::Vertical(Bottom) => 180.0,
+    LineDirection::Horizontal(Left) => 270.0,
+    LineDirection::Corner {
+      horizontal: Right,
+      vertical: Top,
+    } => 45.0,
+    LineDirection::Corner {
+      horizontal: Right,
+      vertical: Bottom,
+    } => 135.0,
+    LineDirection::Corner {
+      horizontal: Left,
+      vertical: Bottom,
+    } => 225.0,
+    LineDirection::Corner {
+      horizontal: Left,
+      vertical: Top,
+    } => 315.0,
+  }
+}
+
+impl From<&CssShapeExtent> for ShapeExtent {
+  fn from(e: &CssShapeExtent) -> Self {
+    match e {
+      CssShapeExtent::ClosestSide => ShapeExtent::ClosestSide,
+      CssShapeExtent::FarthestSide => ShapeExtent::FarthestSide,
+      CssShapeExtent::ClosestCorner => ShapeExtent::ClosestCorner,
+      CssShapeExtent::FarthestCorner => ShapeExtent::FarthestCorner,
+    }
+  }
+}
+
+impl From<&BackgroundRepeatKeyword> for Repeat {
+  fn from(r: &BackgroundRepeatKeyword) -> Self {
+    match r {
+      BackgroundRepeatKeyword::Repeat => Repeat::Repeat,
+      BackgroundRepeatKeyword::Space => Repeat::Space,
+      BackgroundRepeatKeyword::Round => Repeat::Round,
+      BackgroundRepeatKeyword::NoRepeat => Repeat::NoRepeat,
+    }
+  }
+}
+
+/// Shadow of `box-shadow` or `text-shadow`, in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Shadow {
+  pub x: f32,
+  pub y: f32,
+  pub blur: f32,
+  pub spread: f32,
+  /// `None` for `currentColor`.
+  pub color: Option<Color>,
+  /// Drawn inside the box. Never set for text shadows.
+  pub inset: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextTransform {
+  #[default]
+  None,
+  Uppercase,
+  Lowercase,
+  /// First letter of each word in uppercase.
+  Capitalize,
+}
+
+impl TextTransform {
+  /// Transform the case of `text`.
+  pub fn transform<'a>(self, text: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+    let text = text.into();
+    match self {
+      TextTransform::None => text,
+      TextTransform::Uppercase => text.to_uppercase().into(),
+      TextTransform::Lowercase => text.to_lowercase().into(),
+      TextTransform::Capitalize => {
+        let mut word_start = true;
+        let mut result = String::with_capacity(text.len());
+        for c in text.chars() {
+          if word_start {
+            result.extend(c.to_uppercase());
+          } else {
+            result.push(c);
+          }
+          word_start = c.is_whitespace();
+        }
+        result.into()
+      },
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextOverflow {
+  #[default]
+  Clip,
+  Ellipsis,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WhiteSpace {
+  #[default]
+  Normal,
+  Pre,
+  NoWrap,
+  PreWrap,
+  BreakSpaces,
+  PreLine,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextWrap {
+  #[default]
+  Wrap,
+  NoWrap,
+  Balance,
+  Pretty,
+  Stable,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WordBreak {
+  #[default]
+  Normal,
+  KeepAll,
+  BreakAll,
+  BreakWord,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextIndent {
+  pub length: Length,
+  /// Indent all the lines but the first one.
+  pub hanging: bool,
+  /// Indent the lines after forced line breaks too.
+  pub each_line: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextJustify {
+  #[default]
+  Auto,
+  None,
+  InterWord,
+  InterCharacter,
+}
+
+/// What relative lengths are resolved against.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Context {
+  /// Font size of the root element, for `rem`, and for `em` when no font
+  /// size is inherited.
+  pub root_font_size: f32,
+  /// Width and height of the viewport, for `vw`, `vh`, `vmin` and `vmax`.
+  pub viewport: Option<(f32, f32)>,
+  /// Width and height of the containing block, for percentages.
+  pub containing_block: Option<(f32, f32)>,
+  /// Device pixels per CSS pixel.
+  pub scale_factor: f32,
+}
+
+impl Default for Context {
+  fn default() -> Self {
+    Context {
+      root_font_size: 16.0,
+      viewport: None,
+      containing_block: None,
+      scale_factor: 1.0,
+    }
+  }
+}
+
+impl Context {
+  /// Border widths are rounded down to whole device pixels, but never to
+  /// less than one device pixel.
+  pub(crate) fn snap(&self, width: f32) -> f32 {
+    if width <= 0.0 {
+      0.0
+    } else {
+      (width * self.scale_factor).floor().max(1.0) / self.scale_factor
+    }
+  }
+}
+
+/// Outer and inner display type, as far as layout is concerned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Display {
+  #[default]
+  Flow,
+  Flex,
+  Grid,
+  None,
+}
+
+/// Size of a grid track, or of one of the bounds of `minmax()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrackBreadth {
+  Length(Length),
+  /// Flexible length, in `fr`.
+  Fraction(f32),
+  MinContent,
+  MaxContent,
+  Auto,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrackSize {
+  Breadth(TrackBreadth),
+  MinMax(TrackBreadth, TrackBreadth),
+  FitContent(Length),
+}
+
+impl Default for TrackSize {
+  fn default() -> Self {
+    TrackSize::Breadth(TrackBreadth::Auto)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepeatCount {
+  Count(u32),
+  AutoFill,
+  AutoFit,
+}
+
+/// Track or `repeat()` of a track list.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Track {
+  Size(TrackSize),
+  Repeat {
+    count: RepeatCount,
+    /// Names of the lines around the repeated tracks, see [`TrackList`].
+    line_names: Vec<Vec<String>>,
+    sizes: Vec<TrackSize>,
+  },
+}
+
+/// Explicit grid tracks. Empty for `none`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrackList {
+  /// Names of the lines before, between and after the tracks: there is one
+  /// more item than in `tracks`.
+  pub line_names: Vec<Vec<String>>,
+  pub tracks: Vec<Track>,
+}
+
+/// Named grid areas (`grid-template-areas`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GridAreas {
+  pub columns: u32,
+  /// Area names, row by row. `None` for `.` cells.
+  pub areas: Vec<Option<String>>,
+}
+
+impl GridAreas {
+  /// Row and column lines (starting at 1) bounding the area `name`.
+  pub fn lines(&self, name: &str) -> Option<(GridPlacement, GridPlacement)> {
+    let columns = self.columns.max(1) as usize;
+    let cells = self.areas.iter().enumerate().filter(|(_, area)| area.as_deref() == Some(name));
+    let (rows, cols) = cells.fold((None, None), |(rows, cols), (i, _)| {
+      let extend = |range: Option<(usize, usize)>, v: usize| Some(range.map_or((v, v), |(a, b)| (a.min(v), b.max(v))));
+      (extend(rows, i / columns), extend(cols, i % columns))
+    });
+    let placement = |(start, end): (usize, usize)| {
+      GridPlacement {
+        start: GridLine::Line(start as i32 + 1, None),
+        end: GridLine::Line(end as i32 + 2, None),
+      }
+    };
+    Some((placement(rows?), placement(cols?)))
+  }
+}
+
+/// Direction in which auto-placed grid items are laid out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AutoFlow {
+  pub column: bool,
+  pub dense: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum GridLine {
+  #[default]
+  Auto,
+  /// Named area, or line name.
+  Area(String),
+  /// Line number, counted from the end if negative, optionally restricted to
+  /// lines with that name.
+  Line(i32, Option<String>),
+  Span(i32, Option<String>),
+}
+
+/// Placement of an item in the grid rows or columns.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GridPlacement {
+  pub start: GridLine,
+  pub end: GridLine,
+}
+
+/// Font family, or generic font family.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FontFamily {
+  Named(String),
+  Serif,
+  SansSerif,
+  Cursive,
+  Fantasy,
+  Monospace,
+  SystemUi,
+  UiSerif,
+  UiSansSerif,
+  UiMonospace,
+  UiRounded,
+  Emoji,
+  Math,
+  FangSong,
+}
+
+impl FontFamily {
+  fn from_css(f: &CssFontFamily<'_>) -> Option<FontFamily> {
+    use GenericFontFamily as G;
+    Some(match f {
+      CssFontFamily::FamilyName(name) => FontFamily::Named(name.to_string()),
+      CssFontFamily::Generic(G::Serif) => FontFamily::Serif,
+      CssFontFamily::Generic(G::SansSerif) => FontFamily::SansSerif,
+      CssFontFamily::Generic(G::Cursive) => FontFamily::Cursive,
+      CssFontFamily::Generic(G::Fantasy) => FontFamily::Fantasy,
+      CssFontFamily::Generic(G::Monospace) => FontFamily::Monospace,
+      CssFontFamily::Generic(G::SystemUI) => FontFamily::SystemUi,
+      CssFontFamily::Generic(G::UISerif) => FontFamily::UiSerif,
+      CssFontFamily::Generic(G::UISansSerif) => FontFamily::UiSansSerif,
+      CssFontFamily::Generic(G::UIMonospace) => FontFamily::UiMonospace,
+      CssFontFamily::Generic(G::UIRounded) => FontFamily::UiRounded,
+      CssFontFamily::Generic(G::Emoji) => FontFamily::Emoji,
+      CssFontFamily::Generic(G::Math) => FontFamily::Math,
+      CssFontFamily::Generic(G::FangSong) => FontFamily::FangSong,
+      // CSS-wide keywords are not families.
+      CssFontFamily::Generic(_) => return None,
+    })
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FontStyle {
+  #[default]
+  Normal,
+  Italic,
+  /// Angle in degrees.
+  Oblique(f32),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineHeight {
+  #[default]
+  Normal,
+  /// Multiple of the font size.
+  Number(f32),
+  Length(Length),
+}
+
+/// Font declared with `@font-face`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFace {
+  pub family: String,
+  /// Sources, by order of preference.
+  pub sources: Vec<FontSource>,
+  pub style: FontStyle,
+  /// Range of supported weights.
+  pub weight: (f32, f32),
+  /// Range of supported widths, `1.0` being `normal`.
+  pub stretch: (f32, f32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FontSource {
+  /// `url()`, resolved against the directory of the stylesheet.
+  Url { url: String, format: Option<String> },
+  /// `local()`: font installed on the system.
+  Local(String),
+}
+
+impl FontFace {
+  pub(crate) fn new(rule: &FontFaceRule<'_>, base: Option<&Path>) -> FontFace {
+    let mut face = FontFace {
+      family: String::new(),
+      sources: vec![],
+      style: FontStyle::Normal,
+      weight: (400.0, 400.0),
+      stretch: (1.0, 1.0),
+    };
+    for p in &rule.properties {
+      match p {
+        FontFaceProperty::FontFamily(f) => {
+          if let Some(FontFamily::Named(name)) = FontFamily::from_css(f) {
+            face.family = name;
+          }
+        },
+        FontFaceProperty::Source(sources) => {
+          face.sources = sources
+            .iter()
+            .filter_map(|s| {
+              match s {
+                Source::Url(UrlSource { url, format, .. }) => {
+                  Some(FontSource::Url {
+                    url: resolve_url(url.url.as_ref(), base),
+                    format: format.as_ref().map(font_format),
+                  })
+                },
+                Source::Local(f) => {
+                  match FontFamily::from_css(f) {
+                    Some(FontFamily::Named(name)) => Some(FontSource::Local(name)),
+                    _ => None,
+                  }
+                },
+              }
+            })
+            .collect()
+        },
+        FontFaceProperty::FontStyle(FontFaceStyle::Normal) => face.style = FontStyle::Normal,
+        FontFaceProperty::FontStyle(FontFaceStyle::Italic) => face.style = FontStyle::Italic,
+        FontFaceProperty::FontStyle(FontFaceStyle::Oblique(Size2D(a, _))) => face.style = FontStyle::Oblique(a.to_degrees()),
+        FontFaceProperty::FontWeight(Size2D(a, b)) => face.weight = (font_weight(a, 400.0), font_weight(b, 400.0)),
+        FontFaceProperty::FontStretch(Size2D(a, b)) => face.stretch = (font_stretch(a), font_stretch(b)),
+        _ => {},
+      }
+    }
+    face
+  }
+}
+
+fn font_format(f: &FontFormat<'_>) -> String {
+  match f {
+    FontFormat::WOFF => "woff".to_owned(),
+    FontFormat::WOFF2 => "woff2".to_owned(),
+    FontFormat::TrueType => "truetype".to_owned(),
+    FontFormat::OpenType => "opentype".to_owned(),
+    FontFormat::EmbeddedOpenType => "embedded-opentype".to_owned(),
+    FontFormat::Collection => "collection".to_owned(),
+    FontFormat::SVG => "svg".to_owned(),
+    FontFormat::String(s) => s.to_string(),
+  }
+}
+
+/// Numeric weight. `bolder` and `lighter` are relative to `parent`.
+/// See <https://drafts.csswg.org/css-fonts/#relative-weights>
+fn font_weight(w: &CssFontWeight, parent: f32) -> f32 {
+  match w {
+    CssFontWeight::Absolute(AbsoluteFontWeight::Weight(w)) => *w,
+    CssFontWeight::Absolute(AbsoluteFontWeight::Normal) => 400.0,
+    CssFontWeight::Absolute(AbsoluteFontWeight::Bold) => 700.0,
+    CssFontWeight::Bolder if parent < 350.0 => 400.0,
+    CssFontWeight::Bolder if parent < 550.0 => 700.0,
+    CssFontWeight::Bolder => parent.max(900.0),
+    CssFontWeight::Lighter if parent < 100.0 => parent,
+    CssFontWeight::Lighter if parent < 550.0 => 100.0,
+    CssFontWeight::Lighter if parent < 750.0 => 400.0,
+    CssFontWeight::Lighter => 700.0,
+  }
+}
+
+fn font_stretch(s: &CssFontStretch) -> f32 {
+  let p: Percentage = s.into();
+  p.0
+}
+
+/// Whether an element is drawn. Hidden elements still take space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Visibility {
+  #[default]
+  Visible,
+  Hidden,
+  /// Like `Hidden`, except for table rows and columns.
+  Collapse,
+}
+
+/// Whether an element can be the target of pointer events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointerEvents {
+  #[default]
+  Auto,
+  None,
+}
+
+/// Mouse cursor over an element.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cursor {
+  /// Images to try first, in order.
+  pub images: Vec<CursorImage>,
+  /// Cursor used when none of the images can be loaded.
+  pub keyword: CursorKeyword,
+}
+
+/// Cursor image of a `url()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CursorImage {
+  /// Resolved against the directory of the stylesheet.
+  pub url: String,
+  /// Position of the pointer in the image, from its top-left corner.
+  pub hotspot: Option<(f32, f32)>,
+}
+
+/// Predefined cursor.
+/// See <https://drafts.csswg.org/css-ui/#cursor>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorKeyword {
+  /// The toolkit decides, usually `Text` over text and `Default` elsewhere.
+  #[default]
+  Auto,
+  Default,
+  None,
+  ContextMenu,
+  Help,
+  Pointer,
+  Progress,
+  Wait,
+  Cell,
+  Crosshair,
+  Text,
+  VerticalText,
+  Alias,
+  Copy,
+  Move,
+  NoDrop,
+  NotAllowed,
+  Grab,
+  Grabbing,
+  EResize,
+  NResize,
+  NeResize,
+  NwResize,
+  SResize,
+  SeResize,
+  SwResize,
+  WResize,
+  EwResize,
+  NsResize,
+  NeswResize,
+  NwseResize,
+  ColResize,
+  RowResize,
+  AllScroll,
+  ZoomIn,
+  ZoomOut,
+}
+
+impl From<&CssCursorKeyword> for CursorKeyword {
+  fn from(k: &CssCursorKeyword) -> Self {
+    use CssCursorKeyword as K;
+    match k {
+      K::Auto => CursorKeyword::Auto,
+      K::Default => CursorKeyword::Default,
+      K::None => CursorKeyword::None,
+      K::ContextMenu => CursorKeyword::ContextMenu,
+      K::Help => CursorKeyword::Help,
+      K::Pointer => CursorKeyword::Pointer,
+      K::Progress => CursorKeyword::Progress,
+      K::Wait => CursorKeyword::Wait,
+      K::Cell => CursorKeyword::Cell,
+      K::Crosshair => CursorKeyword::Crosshair,
+      K::Text => CursorKeyword::Text,
+      K::VerticalText => CursorKeyword::VerticalText,
+      K::Alias => CursorKeyword::Alias,
+      K::Copy => CursorKeyword::Copy,
+      K::Move => CursorKeyword::Move,
+      K::NoDrop => CursorKeyword::NoDrop,
+      K::NotAllowed => CursorKeyword::NotAllowed,
+      K::Grab => CursorKeyword::Grab,
+      K::Grabbing => CursorKeyword::Grabbing,
+      K::EResize => CursorKeyword::EResize,
+      K::NResize => CursorKeyword::NResize,
+      K::NeResize => CursorKeyword::NeResize,
+      K::NwResize => CursorKeyword::NwResize,
+      K::SResize => CursorKeyword::SResize,
+      K::SeResize => CursorKeyword::SeResize,
+      K::SwResize => CursorKeyword::SwResize,
+      K::WResize => CursorKeyword::WResize,
+      K::EwResize => CursorKeyword::EwResize,
+      K::NsResize => CursorKeyword::NsResize,
+      K::NeswResize => CursorKeyword::NeswResize,
+      K::NwseResize => CursorKeyword::NwseResize,
+      K::ColResize => CursorKeyword::ColResize,
+      K::RowResize => CursorKeyword::RowResize,
+      K::AllScroll => CursorKeyword::AllScroll,
+      K::ZoomIn => CursorKeyword::ZoomIn,
+      K::ZoomOut => CursorKeyword::ZoomOut,
+    }
+  }
+}
+
+impl Cursor {
+  fn new(c: &CssCursor<'_>, base: Option<&Path>) -> Cursor {
+    Cursor {
+      images: c
+        .images
+        .iter()
+        .map(|image| {
+          CursorImage {
+            url: resolve_url(image.url.url.as_ref(), base),
+            hotspot: image.hotspot,
+          }
+        })
+        .collect(),
+      keyword: (&c.keyword).into(),
+    }
+  }
+}
+
+/// Content generated by the `::before` and `::after` pseudo-elements
+/// (`content`).
+/// See <https://drafts.csswg.org/css-content/#content-property>
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum GeneratedContent {
+  /// No content for `::before` and `::after`.
+  #[default]
+  Normal,
+  None,
+  Items(Vec<ContentItem>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContentItem {
+  Text(String),
+  /// `url()`, resolved against the directory of the stylesheet.
+  Url(String),
+  /// `counter(name)` or `counter(name, style)`. Counters are not maintained
+  /// by guicss, the toolkit numbers the elements.
+  Counter {
+    name: String,
+    style: Option<String>,
+  },
+  /// `attr(name)` or `attr(name, "fallback")`. Replaced by `Text` when an
+  /// element is computed, with the value of its attribute, or of the
+  /// attribute of its originating element for pseudo-elements.
+  Attr {
+    name: String,
+    fallback: String,
+  },
+}
+
+impl GeneratedContent {
+  /// Whether a `::before` or `::after` pseudo-element is generated.
+  pub fn is_generated(&self) -> bool {
+    matches!(self, GeneratedContent::Items(_))
+  }
+
+  /// The text of the content, without its images and counters.
+  pub fn text(&self) -> String {
+    match self {
+      GeneratedContent::Items(items) => {
+        items
+          .iter()
+          .filter_map(|item| {
+            match item {
+              ContentItem::Text(text) => Some(text.as_str()),
+              _ => None,
+            }
+          })
+          .collect()
+      },
+      _ => String::new(),
+    }
+  }
+
+  fn new(tokens: &TokenList<'_>, base: Option<&Path>) -> Result<GeneratedContent> {
+    match single_ident(tokens) {
+      Some("normal") => return Ok(GeneratedContent::Normal),
+      Some("none") => return Ok(GeneratedContent::None),
+      _ => {},
+    }
+    let mut items = vec![];
+    for token in tokens.0.iter().filter(|t| !matches!(t, TokenOrValue::Token(Token::WhiteSpace(_)))) {
+      let item = match token {
+        TokenOrValue::Token(Token::String(s)) => ContentItem::Text(s.to_string()),
+        TokenOrValue::Url(url) => ContentItem::Url(resolve_url(url.url.as_ref(), base)),
+        TokenOrValue::Function(f) => {
+          let mut arguments = f.arguments.0.iter().filter(|t| !matches!(t, TokenOrValue::Token(Token::WhiteSpace(_))));
+          let name = match arguments.next() {
+            Some(TokenOrValue::Token(Token::Ident(name))) => name.to_string(),
+            _ => bail!("Unsupported content value: {}() without a name", f.name.0),
+          };
+          let second = match (arguments.next(), arguments.next()) {
+            (None, None) => None,
+            (Some(TokenOrValue::Token(Token::Comma)), Some(TokenOrValue::Token(Token::String(s) | Token::Ident(s)))) => Some(s.to_string()),
+            _ => bail!("Unsupported content value: invalid {}() arguments", f.name.0),
+          };
+          match f.name.0.as_ref() {
+            "attr" => {
+              ContentItem::Attr {
+                name,
+                fallback: second.unwrap_or_default(),
+              }
+            },
+            "counter" => ContentItem::Counter { name, style: second },
+            unknown => bail!("Unsupported content value: {unknown}()"),
+          }
+        },
+        // Alternative text, for accessibility.
+        TokenOrValue::Token(Token::Delim('/')) => break,
+        _ => bail!("Unsupported content value"),
+      };
+      items.push(item);
+    }
+    Ok(GeneratedContent::Items(items))
+  }
+
+  /// Replace `attr()` with the values of `attribute`.
+  pub(crate) fn resolve_attributes<'a>(&mut self, attribute: impl Fn(&str) -> Option<&'a str>) {
+    if let GeneratedContent::Items(items) = self {
+      for item in items.iter_mut() {
+        if let ContentItem::Attr { name, fallback } = item {
+          let text = attribute(name).map_or_else(|| std::mem::take(fallback), str::to_owned);
+          *item = ContentItem::Text(text);
+        }
+      }
+    }
+  }
+}
+
+/// Property a transition applies to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransitionProperty {
+  All,
+  /// Name of a property, possibly a shorthand.
+  Property(String),
+}
+
+impl TransitionProperty {
+  /// Whether the transition applies to the longhand property `name`. A
+  /// shorthand covers its longhands: `border` and `border-color` both cover
+  /// `border-top-color`.
+  pub fn covers(&self, name: &str) -> bool {
+    match self {
+      TransitionProperty::All => true,
+      TransitionProperty::Property(p) => {
+        let id = PropertyId::from(name);
+        let property = PropertyId::from(p.as_str());
+        property == id || is_longhand_of(&id, &property)
+      },
+    }
+  }
+}
+
+/// Longhands of `id`, or `id` itself if it is not a shorthand.
+pub(crate) fn longhands(id: &PropertyId<'_>) -> Vec<PropertyId<'static>> {
+  match id.longhands() {
+    Some(ids) => ids.iter().flat_map(longhands).collect(),
+    None => vec![id.clone().into_owned()],
+  }
+}
+
+/// Whether `id` is a longhand of `shorthand`, including the longhands of its
+/// longhands that are shorthands themselves.
+fn is_longhand_of(id: &PropertyId<'_>, shorthand: &PropertyId<'_>) -> bool {
+  shorthand
+    .longhands()
+    .map_or(false, |longhands| longhands.iter().any(|l| l == id || is_longhand_of(id, l)))
+}
+
+/// Where the jumps of a `steps()` timing function happen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepPosition {
+  Start,
+  #[default]
+  End,
+  JumpNone,
+  JumpBoth,
+}
+
+/// Progress of a transition or an animation over time.
+/// See <https://drafts.csswg.org/css-easing/>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimingFunction {
+  Linear,
+  /// `x1, y1, x2, y2`. Keywords like `ease-in` are cubic Bézier curves.
+  CubicBezier(f32, f32, f32, f32),
+  Steps(u32, StepPosition),
+}
+
+/// `ease`.
+impl Default for TimingFunction {
+  fn default() -> Self {
+    TimingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0)
+  }
+}
+
+impl From<&EasingFunction> for TimingFunction {
+  fn from(e: &EasingFunction) -> Self {
+    match *e {
+      EasingFunction::Linear => TimingFunction::Linear,
+      EasingFunction::Ease => TimingFunction::default(),
+      EasingFunction::EaseIn => TimingFunction::CubicBezier(0.42, 0.0, 1.0, 1.0),
+      EasingFunction::EaseOut => TimingFunction::CubicBezier(0.0, 0.0, 0.58, 1.0),
+      EasingFunction::EaseInOut => TimingFunction::CubicBezier(0.42, 0.0, 0.58, 1.0),
+      EasingFunction::CubicBezier { x1, y1, x2, y2 } => TimingFunction::CubicBezier(x1, y1, x2, y2),
+      EasingFunction::Steps { count, position } => {
+        let position = match position {
+          CssStepPosition::Start => StepPosition::Start,
+          CssStepPosition::End => StepPosition::End,
+          CssStepPosition::JumpNone => StepPosition::JumpNone,
+          CssStepPosition::JumpBoth => StepPosition::JumpBoth,
+        };
+        TimingFunction::Steps(count.max(1) as u32, position)
+      },
+    }
+  }
+}
+
+impl TimingFunction {
+  /// Output progress for the input progress `t`, between 0 and 1.
+  pub fn progress(&self, t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    match *self {
+      TimingFunction::Linear => t,
+      TimingFunction::CubicBezier(x1, y1, x2, y2) => {
+        let bezier = |a: f32, b: f32, s: f32| 3.0 * a * s * (1.0 - s).powi(2) + 3.0 * b * s * s * (1.0 - s) + s.powi(3);
+        // x is monotonic as x1 and x2 are between 0 and 1: find the curve
+        // parameter by bisection.
+        let (mut low, mut high, mut s) = (0.0, 1.0, t);
+        for _ in 0..32 {
+          let x = bezier(x1, x2, s);
+          if (x - t).abs() < 1e-5 {
+            break;
+          }
+          if x < t {
+            low = s;
+          } else {
+            high = s;
+          }
+          s = (low + high) / 2.0;
+        }
+        bezier(y1, y2, s)
+      },
+      TimingFunction::Steps(count, position) => {
+        let count = count as f32;
+        let jumps = match position {
+          StepPosition::JumpNone => (count - 1.0).max(1.0),
+          StepPosition::JumpBoth => count + 1.0,
+          StepPosition::Start | StepPosition::End => count,
+        };
+        let mut step = (t * count).floor();
+        if matches!(position, StepPosition::Start | StepPosition::JumpBoth) {
+          step += 1.0;
+        }
+        step.clamp(0.0, jumps) / jumps
+      },
     }
   }
 }
 
-impl Default for Color {
+/// Transition of one property, combining the `transition-*` properties.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyTransition {
+  pub property: TransitionProperty,
+  /// In seconds.
+  pub duration: f32,
+  /// In seconds, negative to start the transition part-way.
+  pub delay: f32,
+  pub timing_function: TimingFunction,
+}
+
+/// Number of times an animation plays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IterationCount {
+  Count(f32),
+  Infinite,
+}
+
+impl Default for IterationCount {
   fn default() -> Self {
-    Color::transparent()
+    IterationCount::Count(1.0)
   }
 }
 
-impl From<&CssColor> for Color {
-  fn from(c: &CssColor) -> Self {
-    RGBA::from(c).into()
-  }
+/// Whether iterations of an animation play forwards or backwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnimationDirection {
+  #[default]
+  Normal,
+  Reverse,
+  /// Forwards, then backwards.
+  Alternate,
+  /// Backwards, then forwards.
+  AlternateReverse,
 }
 
-/// Layout direction.
-#[derive(Debug, Default, PartialEq, Eq)]
-pub enum Direction {
+/// Whether an animation applies before it starts and after it ends.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FillMode {
   #[default]
-  Horizontal,
-  Vertical,
+  None,
+  Forwards,
+  Backwards,
+  Both,
 }
 
-/// Four values for each corner of an element.
-#[derive(Debug, Default, PartialEq, Eq)]
-pub struct Corners<T>
-where T: Default
-{
-  pub nw: (T, T),
-  pub ne: (T, T),
-  pub sw: (T, T),
-  pub se: (T, T),
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayState {
+  #[default]
+  Running,
+  Paused,
 }
 
-/// Width and color definition.
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
-pub struct WidthAndColor {
-  pub width: f32,
-  pub color: Color,
+/// Animation of an element, combining the `animation-*` properties.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+  /// Name of the `@keyframes` rule.
+  pub name: String,
+  /// Of one iteration, in seconds.
+  pub duration: f32,
+  /// In seconds, negative to start the animation part-way.
+  pub delay: f32,
+  /// Applies between keyframes, unless a keyframe sets its own.
+  pub timing_function: TimingFunction,
+  pub iteration_count: IterationCount,
+  pub direction: AnimationDirection,
+  pub fill_mode: FillMode,
+  /// Paused animations are the application's business: it stops
+  /// advancing the time it samples the animation at.
+  pub play_state: PlayState,
 }
 
-/// Four values for each side of an element.
-#[derive(Debug, Default, PartialEq, Eq)]
-pub struct Sides<T>
-where T: Default
-{
-  pub top: T,
-  pub right: T,
-  pub bottom: T,
-  pub left: T,
+/// Resolve a relative `url()` against the directory of the stylesheet.
+/// Absolute paths and URLs with a scheme are kept as they are.
+pub(crate) fn resolve_url(url: &str, base: Option<&Path>) -> String {
+  let has_scheme = url.split_once(':').map_or(false, |(scheme, _)| !scheme.is_empty() && !scheme.contains('/'));
+  match base {
+    Some(base) if !has_scheme && !url.starts_with('/') => base.join(url).to_string_lossy().to_string(),
+    _ => url.to_owned(),
+  }
 }
 
-/// Direction-agnostic alignment
-#[derive(Debug, Default, PartialEq, Eq)]
-pub enum Align {
-  #[default]
-  Start,
-  Center,
-  End,
-  Justify,
+/// CSS-wide keywords, valid for every property.
+/// See <https://drafts.csswg.org/css-cascade-5/#defaulting-keywords>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WideKeyword {
+  Initial,
+  Inherit,
+  Unset,
+  /// There is only one origin (author) and no cascade layers, so `revert`
+  /// and `revert-layer` behave like `unset`.
+  Revert,
+}
+
+impl WideKeyword {
+  /// Keyword of `prop: keyword` and `all: keyword` declarations, including
+  /// the ones of properties unknown to the parser.
+  pub(crate) fn of(p: &Property<'_>) -> Option<WideKeyword> {
+    match p {
+      Property::Unparsed(p) => WideKeyword::from_tokens(&p.value),
+      Property::Custom(CustomProperty { name, value }) if !name.as_ref().starts_with("--") => WideKeyword::from_tokens(value),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn from_tokens(tokens: &TokenList<'_>) -> Option<WideKeyword> {
+    match single_ident(tokens)? {
+      "initial" => Some(WideKeyword::Initial),
+      "inherit" => Some(WideKeyword::Inherit),
+      "unset" => Some(WideKeyword::Unset),
+      "revert" | "revert-layer" => Some(WideKeyword::Revert),
+      _ => None,
+    }
+  }
+}
+
+/// Serialise `tokens`, to parse them as a value unknown to the parser.
+fn tokens_to_string(tokens: &TokenList<'_>) -> Option<String> {
+  let mut source = String::new();
+  let mut printer = Printer::new(&mut source, PrinterOptions::default());
+  tokens.to_css(&mut printer, false).ok()?;
+  Some(source)
+}
+
+/// The identifier of a value made of a single identifier.
+fn single_ident<'a>(tokens: &'a TokenList<'_>) -> Option<&'a str> {
+  let mut tokens = tokens.0.iter().filter(|t| !matches!(t, TokenOrValue::Token(Token::WhiteSpace(_))));
+  match (tokens.next(), tokens.next()) {
+    (Some(TokenOrValue::Token(Token::Ident(ident))), None) => Some(ident.as_ref()),
+    _ => None,
+  }
 }
 
 /// All properties computed for a matching element.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ComputedProperties {
-  pub border_radius: Corners<f32>,
+  /// Horizontal and vertical radii. Percentages, relative to the size of the
+  /// border box, are kept as percentages.
+  pub border_radius: Corners<Length>,
+  /// Widths as declared. See [`ComputedProperties::border_widths`].
   pub border: Sides<WidthAndColor>,
-  pub margin: Sides<f32>,
-  pub padding: Sides<f32>,
+  pub border_style: Sides<BorderStyle>,
+  pub outline: Outline,
+  pub margin: Sides<Length>,
+  pub padding: Sides<Length>,
   pub background_color: Color,
+  /// Background images, top-most first. `None` for `none`. See
+  /// [`ComputedProperties::background_layers`].
+  pub background_image: Vec<Option<Image>>,
+  pub background_position: Vec<Position>,
+  pub background_size: Vec<BackgroundSize>,
+  pub background_repeat: Vec<(Repeat, Repeat)>,
   pub color: Color,
-  pub width: Option<f32>,
-  pub height: Option<f32>,
-  pub min_width: Option<f32>,
-  pub min_height: Option<f32>,
-  pub max_width: Option<f32>,
-  pub max_height: Option<f32>,
+  pub width: Length,
+  pub height: Length,
+  pub min_width: Length,
+  pub min_height: Length,
+  /// `None` for `none`.
+  pub max_width: Option<Length>,
+  /// `None` for `none`.
+  pub max_height: Option<Length>,
   pub direction: Direction,
-  pub flex_basis: Option<f32>,
+  pub wrap: Wrap,
+  pub flex_basis: Length,
   pub flex_grow: f32,
+  pub flex_shrink: f32,
+  pub order: i32,
+  pub justify_content: ContentAlign,
+  pub align_content: ContentAlign,
+  pub align_items: ItemAlign,
+  /// `None` for `auto`: the parent's `align-items`.
+  pub align_self: Option<ItemAlign>,
+  pub row_gap: Length,
+  pub column_gap: Length,
+  pub display: Display,
+  /// Between 0 (transparent) and 1 (opaque).
+  pub opacity: f32,
+  pub visibility: Visibility,
+  pub pointer_events: PointerEvents,
+  pub cursor: Cursor,
+  pub content: GeneratedContent,
+  /// See [`ComputedProperties::transitions`].
+  pub transition_property: Vec<TransitionProperty>,
+  pub transition_duration: Vec<f32>,
+  pub transition_timing_function: Vec<TimingFunction>,
+  pub transition_delay: Vec<f32>,
+  /// `None` for `none`. See [`ComputedProperties::animations`].
+  pub animation_name: Vec<Option<String>>,
+  pub animation_duration: Vec<f32>,
+  pub animation_timing_function: Vec<TimingFunction>,
+  pub animation_iteration_count: Vec<IterationCount>,
+  pub animation_direction: Vec<AnimationDirection>,
+  pub animation_fill_mode: Vec<FillMode>,
+  pub animation_play_state: Vec<PlayState>,
+  pub animation_delay: Vec<f32>,
+  pub grid_template_columns: TrackList,
+  pub grid_template_rows: TrackList,
+  /// `None` for `none`.
+  pub grid_template_areas: Option<GridAreas>,
+  pub grid_auto_columns: Vec<TrackSize>,
+  pub grid_auto_rows: Vec<TrackSize>,
+  pub grid_auto_flow: AutoFlow,
+  pub grid_row: GridPlacement,
+  pub grid_column: GridPlacement,
+  pub justify_items: ItemAlign,
+  /// `None` for `auto`: the parent's `justify-items`.
+  pub justify_self: Option<ItemAlign>,
   pub font_size: Option<f32>,
+  /// By order of preference. Empty for the toolkit's default font.
+  pub font_family: Vec<FontFamily>,
+  pub font_weight: f32,
+  pub font_style: FontStyle,
+  /// `1.0` for `normal`.
+  pub font_stretch: f32,
+  pub line_height: LineHeight,
+  pub letter_spacing: Length,
   pub text_align: Align,
   pub vertical_align: Align,
-  // "text-justify"
-  // "flex-direction"
-  // "flex-wrap"
-  // "flex-flow"
-  // "flex-shrink"
-  // "align-content"
-  // "justify-content"
-  // "align-self"
-  // "justify-self"
-  // "align-items"
-  // "justify-items"
-  // "font-weight"
-  // "font-family"
-  // "font-style"
-
+  /// Front-most shadow first.
+  pub box_shadow: Vec<Shadow>,
+  pub text_shadow: Vec<Shadow>,
+  pub text_decoration: TextDecoration,
+  pub text_transform: TextTransform,
+  pub text_overflow: TextOverflow,
+  pub white_space: WhiteSpace,
+  pub text_wrap: TextWrap,
+  pub word_break: WordBreak,
+  pub text_indent: TextIndent,
+  pub text_justify: TextJustify,
+  /// Custom properties (`--name: value`), with `var()` references
+  /// substituted.
+  pub variables: HashMap<String, TokenList<'static>>,
+  /// Values of the custom properties registered with `@property`, parsed
+  /// with their syntax.
+  pub registered_variables: HashMap<String, ParsedComponent<'static>>,
   // For icons
   // "fill"
   // "stroke"
@@ -143,92 +1688,1135 @@ pub struct ComputedProperties {
   // "stroke-width"
 }
 
+/// Initial values.
+impl Default for ComputedProperties {
+  fn default() -> Self {
+    ComputedProperties {
+      border_radius: Corners::default(),
+      border: Sides::default(),
+      border_style: Sides::default(),
+      outline: Outline::default(),
+      margin: Sides::default(),
+      padding: Sides::default(),
+      background_color: Color::transparent(),
+      background_image: vec![None],
+      background_position: vec![Position::default()],
+      background_size: vec![BackgroundSize::default()],
+      background_repeat: vec![(Repeat::default(), Repeat::default())],
+      color: Color::default(),
+      width: Length::Auto,
+      height: Length::Auto,
+      min_width: Length::Auto,
+      min_height: Length::Auto,
+      max_width: None,
+      max_height: None,
+      direction: Direction::default(),
+      wrap: Wrap::default(),
+      flex_basis: Length::Auto,
+      flex_grow: 0.0,
+      flex_shrink: 1.0,
+      order: 0,
+      justify_content: ContentAlign::default(),
+      align_content: ContentAlign::default(),
+      align_items: ItemAlign::default(),
+      align_self: None,
+      row_gap: Length::default(),
+      column_gap: Length::default(),
+      display: Display::default(),
+      opacity: 1.0,
+      visibility: Visibility::default(),
+      pointer_events: PointerEvents::default(),
+      cursor: Cursor::default(),
+      content: GeneratedContent::Normal,
+      transition_property: vec![TransitionProperty::All],
+      transition_duration: vec![0.0],
+      transition_timing_function: vec![TimingFunction::default()],
+      transition_delay: vec![0.0],
+      animation_name: vec![None],
+      animation_duration: vec![0.0],
+      animation_timing_function: vec![TimingFunction::default()],
+      animation_iteration_count: vec![IterationCount::default()],
+      animation_direction: vec![AnimationDirection::default()],
+      animation_fill_mode: vec![FillMode::default()],
+      animation_play_state: vec![PlayState::default()],
+      animation_delay: vec![0.0],
+      grid_template_columns: TrackList::default(),
+      grid_template_rows: TrackList::default(),
+      grid_template_areas: None,
+      grid_auto_columns: vec![TrackSize::default()],
+      grid_auto_rows: vec![TrackSize::default()],
+      grid_auto_flow: AutoFlow::default(),
+      grid_row: GridPlacement::default(),
+      grid_column: GridPlacement::default(),
+      justify_items: ItemAlign::default(),
+      justify_self: None,
+      font_size: None,
+      font_family: vec![],
+      font_weight: 400.0,
+      font_style: FontStyle::default(),
+      font_stretch: 1.0,
+      line_height: LineHeight::default(),
+      letter_spacing: Length::default(),
+      text_align: Align::default(),
+      vertical_align: Align::default(),
+      box_shadow: vec![],
+      text_shadow: vec![],
+      text_decoration: TextDecoration::default(),
+      text_transform: TextTransform::default(),
+      text_overflow: TextOverflow::default(),
+      white_space: WhiteSpace::default(),
+      text_wrap: TextWrap::default(),
+      word_break: WordBreak::default(),
+      text_indent: TextIndent::default(),
+      text_justify: TextJustify::default(),
+      variables: HashMap::new(),
+      registered_variables: HashMap::new(),
+    }
+  }
+}
+
 impl ComputedProperties {
-  pub(crate) fn apply(&mut self, p: &Property<'_>) -> Result<()> {
-    use lightningcss::properties::border::{BorderColor, BorderSideWidth, BorderWidth, GenericBorder};
+  /// Starting point of an element whose parent is `parent`: inherited
+  /// properties and custom properties are taken from the parent, all the
+  /// others are set to their initial value.
+  pub fn inherit(parent: &ComputedProperties) -> ComputedProperties {
+    ComputedProperties {
+      color: parent.color,
+      font_size: parent.font_size,
+      font_family: parent.font_family.clone(),
+      font_weight: parent.font_weight,
+      font_style: parent.font_style,
+      font_stretch: parent.font_stretch,
+      line_height: parent.line_height,
+      letter_spacing: parent.letter_spacing,
+      text_align: parent.text_align,
+      text_shadow: parent.text_shadow.clone(),
+      text_transform: parent.text_transform,
+      white_space: parent.white_space,
+      text_wrap: parent.text_wrap,
+      word_break: parent.word_break,
+      text_indent: parent.text_indent,
+      text_justify: parent.text_justify,
+      visibility: parent.visibility,
+      pointer_events: parent.pointer_events,
+      cursor: parent.cursor.clone(),
+      variables: parent.variables.clone(),
+      registered_variables: parent.registered_variables.clone(),
+      ..ComputedProperties::default()
+    }
+  }
+
+  /// Background layers with an image, top-most first. Positions, sizes and
+  /// repetitions are repeated when there are fewer of them than images.
+  pub fn background_layers(&self) -> Vec<BackgroundLayer> {
+    let images = self.background_image.iter().enumerate();
+    images
+      .filter_map(|(i, image)| {
+        Some(BackgroundLayer {
+          image: image.clone()?,
+          position: layer_value(&self.background_position, i),
+          size: layer_value(&self.background_size, i),
+          repeat: layer_value(&self.background_repeat, i),
+        })
+      })
+      .collect()
+  }
+
+  /// Transitions of the properties listed by `transition-property`. The
+  /// other lists repeat if they are shorter. Transitions without a
+  /// duration are omitted.
+  pub fn transitions(&self) -> Vec<PropertyTransition> {
+    let properties = self.transition_property.iter().enumerate();
+    properties
+      .map(|(i, property)| {
+        PropertyTransition {
+          property: property.clone(),
+          duration: layer_value(&self.transition_duration, i),
+          delay: layer_value(&self.transition_delay, i),
+          timing_function: layer_value(&self.transition_timing_function, i),
+        }
+      })
+      .filter(|t| t.duration > 0.0)
+      .collect()
+  }
+
+  /// Animations listed by `animation-name`, except `none`. The other lists
+  /// repeat if they are shorter.
+  pub fn animations(&self) -> Vec<Animation> {
+    let names = self.animation_name.iter().enumerate();
+    names
+      .filter_map(|(i, name)| {
+        Some(Animation {
+          name: name.clone()?,
+          duration: layer_value(&self.animation_duration, i),
+          delay: layer_value(&self.animation_delay, i),
+          timing_function: layer_value(&self.animation_timing_function, i),
+          iteration_count: layer_value(&self.animation_iteration_count, i),
+          direction: layer_value(&self.animation_direction, i),
+          fill_mode: layer_value(&self.animation_fill_mode, i),
+          play_state: layer_value(&self.animation_play_state, i),
+        })
+      })
+      .collect()
+  }
+
+  /// Whether the element is drawn: it is neither `display: none` nor
+  /// hidden. Transparent elements are drawn.
+  pub fn is_visible(&self) -> bool {
+    self.display != Display::None && self.visibility == Visibility::Visible
+  }
+
+  /// Whether the element receives pointer events.
+  pub fn is_interactive(&self) -> bool {
+    self.is_visible() && self.pointer_events == PointerEvents::Auto
+  }
+
+  /// `color`, or the `color` property for `currentColor` (`None`).
+  pub fn current_color(&self, color: Option<Color>) -> Color {
+    color.unwrap_or(self.color)
+  }
+
+  /// Border widths, zero for sides whose style is `none` or `hidden`.
+  pub fn border_widths(&self) -> Sides<f32> {
+    let width = |side: &WidthAndColor, style: BorderStyle| if style.is_visible() { side.width } else { 0.0 };
+    Sides {
+      top: width(&self.border.top, self.border_style.top),
+      right: width(&self.border.right, self.border_style.right),
+      bottom: width(&self.border.bottom, self.border_style.bottom),
+      left: width(&self.border.left, self.border_style.left),
+    }
+  }
+
+  /// Whether lines of text wrap, according to `white-space` and
+  /// `text-wrap`.
+  pub fn text_wraps(&self) -> bool {
+    !matches!(self.white_space, WhiteSpace::Pre | WhiteSpace::NoWrap) && self.text_wrap != TextWrap::NoWrap
+  }
+
+  /// Properties inherited by default. Must match
+  /// [`ComputedProperties::inherit`].
+  fn is_inherited(id: &PropertyId<'_>) -> bool {
+    use PropertyId as I;
+    if let I::Custom(name) = id {
+      return matches!(name.as_ref(), "text-wrap" | "pointer-events");
+    }
+    matches!(
+      id,
+      I::Color |
+        I::FontSize |
+        I::FontFamily |
+        I::FontWeight |
+        I::FontStyle |
+        I::FontStretch |
+        I::LineHeight |
+        I::Font |
+        I::LetterSpacing |
+        I::TextAlign |
+        I::TextShadow |
+        I::TextTransform |
+        I::WhiteSpace |
+        I::WordBreak |
+        I::TextIndent |
+        I::TextJustify |
+        I::Visibility |
+        I::Cursor
+    )
+  }
+
+  /// Apply a declaration whose value is a CSS-wide keyword, including the
+  /// `all` shorthand. Custom properties are not affected by `all`.
+  pub(crate) fn apply_keyword(&mut self, p: &Property<'_>, keyword: WideKeyword, parent: &ComputedProperties) -> Result<()> {
+    let initial = ComputedProperties::default();
+    match p {
+      Property::Custom(CustomProperty { name, .. }) if name.as_ref() == "all" => {
+        let variables = std::mem::take(&mut self.variables);
+        let registered_variables = std::mem::take(&mut self.registered_variables);
+        *self = match keyword {
+          WideKeyword::Initial => initial,
+          WideKeyword::Inherit => parent.clone(),
+          WideKeyword::Unset | WideKeyword::Revert => ComputedProperties::inherit(parent),
+        };
+        self.variables = variables;
+        self.registered_variables = registered_variables;
+        Ok(())
+      },
+      // Properties unknown to the parser are custom properties.
+      Property::Unparsed(_) | Property::Custom(_) => {
+        let id = p.property_id();
+        let from = match keyword {
+          WideKeyword::Initial => &initial,
+          WideKeyword::Inherit => parent,
+          WideKeyword::Unset | WideKeyword::Revert if Self::is_inherited(&id) => parent,
+          WideKeyword::Unset | WideKeyword::Revert => &initial,
+        };
+        self.copy_property(&id, from)
+      },
+      _ => bail!("Unexpected CSS-wide keyword: {keyword:?}"),
+    }
+  }
+
+  /// Copy the computed value(s) of the property `id` from `from`.
+  pub(crate) fn copy_property(&mut self, id: &PropertyId<'_>, from: &ComputedProperties) -> Result<()> {
+    use PropertyId as I;
+
+    match id {
+      I::FontSize => self.font_size = from.font_size,
+      I::FontFamily => self.font_family = from.font_family.clone(),
+      I::FontWeight => self.font_weight = from.font_weight,
+      I::FontStyle => self.font_style = from.font_style,
+      I::FontStretch => self.font_stretch = from.font_stretch,
+      I::LineHeight => self.line_height = from.line_height,
+      I::Font => {
+        self.font_size = from.font_size;
+        self.font_family = from.font_family.clone();
+        self.font_weight = from.font_weight;
+        self.font_style = from.font_style;
+        self.font_stretch = from.font_stretch;
+        self.line_height = from.line_height;
+      },
+      I::LetterSpacing => self.letter_spacing = from.letter_spacing,
+      I::TextAlign => self.text_align = from.text_align,
+      I::BoxShadow(_) => self.box_shadow = from.box_shadow.clone(),
+      I::TextShadow => self.text_shadow = from.text_shadow.clone(),
+      I::TextDecorationLine(_) => {
+        self.text_decoration.underline = from.text_decoration.underline;
+        self.text_decoration.overline = from.text_decoration.overline;
+        self.text_decoration.line_through = from.text_decoration.line_through;
+      },
+      I::TextDecorationStyle(_) => self.text_decoration.style = from.text_decoration.style,
+      I::TextDecorationColor(_) => self.text_decoration.color = from.text_decoration.color,
+      I::TextDecorationThickness => self.text_decoration.thickness = from.text_decoration.thickness,
+      I::TextDecoration(_) => self.text_decoration = from.text_decoration,
+      I::TextTransform => self.text_transform = from.text_transform,
+      I::TextOverflow(_) => self.text_overflow = from.text_overflow,
+      I::WhiteSpace => self.white_space = from.white_space,
+      I::WordBreak => self.word_break = from.word_break,
+      I::TextIndent => self.text_indent = from.text_indent,
+      I::TextJustify => self.text_justify = from.text_justify,
+      I::VerticalAlign => self.vertical_align = from.vertical_align,
+      I::Width => self.width = from.width,
+      I::Height => self.height = from.height,
+      I::MinWidth => self.min_width = from.min_width,
+      I::MinHeight => self.min_height = from.min_height,
+      I::MaxWidth => self.max_width = from.max_width,
+      I::MaxHeight => self.max_height = from.max_height,
+      I::FlexDirection(_) => self.direction = from.direction,
+      I::FlexWrap(_) => self.wrap = from.wrap,
+      I::FlexFlow(_) => {
+        self.direction = from.direction;
+        self.wrap = from.wrap;
+      },
+      I::FlexGrow(_) => self.flex_grow = from.flex_grow,
+      I::FlexShrink(_) => self.flex_shrink = from.flex_shrink,
+      I::FlexBasis(_) => self.flex_basis = from.flex_basis,
+      I::Flex(_) => {
+        self.flex_grow = from.flex_grow;
+        self.flex_shrink = from.flex_shrink;
+        self.flex_basis = from.flex_basis;
+      },
+      I::Order(_) => self.order = from.order,
+      I::JustifyContent(_) => self.justify_content = from.justify_content,
+      I::AlignContent(_) => self.align_content = from.align_content,
+      I::PlaceContent => {
+        self.justify_content = from.justify_content;
+        self.align_content = from.align_content;
+      },
+      I::AlignItems(_) => self.align_items = from.align_items,
+      I::AlignSelf(_) => self.align_self = from.align_self,
+      I::RowGap => self.row_gap = from.row_gap,
+      I::ColumnGap => self.column_gap = from.column_gap,
+      I::Gap => {
+        self.row_gap = from.row_gap;
+        self.column_gap = from.column_gap;
+      },
+      I::Display => self.display = from.display,
+      I::Opacity => self.opacity = from.opacity,
+      I::Visibility => self.visibility = from.visibility,
+      I::Cursor => self.cursor = from.cursor.clone(),
+      I::TransitionProperty(_) => self.transition_property = from.transition_property.clone(),
+      I::TransitionDuration(_) => self.transition_duration = from.transition_duration.clone(),
+      I::TransitionTimingFunction(_) => self.transition_timing_function = from.transition_timing_function.clone(),
+      I::TransitionDelay(_) => self.transition_delay = from.transition_delay.clone(),
+      I::Transition(_) => {
+        self.transition_property = from.transition_property.clone();
+        self.transition_duration = from.transition_duration.clone();
+        self.transition_timing_function = from.transition_timing_function.clone();
+        self.transition_delay = from.transition_delay.clone();
+      },
+      I::AnimationName(_) => self.animation_name = from.animation_name.clone(),
+      I::AnimationDuration(_) => self.animation_duration = from.animation_duration.clone(),
+      I::AnimationTimingFunction(_) => self.animation_timing_function = from.animation_timing_function.clone(),
+      I::AnimationIterationCount(_) => self.animation_iteration_count = from.animation_iteration_count.clone(),
+      I::AnimationDirection(_) => self.animation_direction = from.animation_direction.clone(),
+      I::AnimationFillMode(_) => self.animation_fill_mode = from.animation_fill_mode.clone(),
+      I::AnimationPlayState(_) => self.animation_play_state = from.animation_play_state.clone(),
+      I::AnimationDelay(_) => self.animation_delay = from.animation_delay.clone(),
+      I::Animation(_) => {
+        self.animation_name = from.animation_name.clone();
+        self.animation_duration = from.animation_duration.clone();
+        self.animation_timing_function = from.animation_timing_function.clone();
+        self.animation_iteration_count = from.animation_iteration_count.clone();
+        self.animation_direction = from.animation_direction.clone();
+        self.animation_fill_mode = from.animation_fill_mode.clone();
+        self.animation_play_state = from.animation_play_state.clone();
+        self.animation_delay = from.animation_delay.clone();
+      },
+      I::GridTemplateColumns => self.grid_template_columns = from.grid_template_columns.clone(),
+      I::GridTemplateRows => self.grid_template_rows = from.grid_template_rows.clone(),
+      I::GridTemplateAreas => self.grid_template_areas = from.grid_template_areas.clone(),
+      I::GridTemplate => {
+        self.grid_template_columns = from.grid_template_columns.clone();
+        self.grid_template_rows = from.grid_template_rows.clone();
+        self.grid_template_areas = from.grid_template_areas.clone();
+      },
+      I::GridAutoColumns => self.grid_auto_columns = from.grid_auto_columns.clone(),
+      I::GridAutoRows => self.grid_auto_rows = from.grid_auto_rows.clone(),
+      I::GridAutoFlow => self.grid_auto_flow = from.grid_auto_flow,
+      I::Grid => {
+        self.grid_template_columns = from.grid_template_columns.clone();
+        self.grid_template_rows = from.grid_template_rows.clone();
+        self.grid_template_areas = from.grid_template_areas.clone();
+        self.grid_auto_columns = from.grid_auto_columns.clone();
+        self.grid_auto_rows = from.grid_auto_rows.clone();
+        self.grid_auto_flow = from.grid_auto_flow;
+      },
+      I::GridRowStart => self.grid_row.start = from.grid_row.start.clone(),
+      I::GridRowEnd => self.grid_row.end = from.grid_row.end.clone(),
+      I::GridColumnStart => self.grid_column.start = from.grid_column.start.clone(),
+      I::GridColumnEnd => self.grid_column.end = from.grid_column.end.clone(),
+      I::GridRow => self.grid_row = from.grid_row.clone(),
+      I::GridColumn => self.grid_column = from.grid_column.clone(),
+      I::GridArea => {
+        self.grid_row = from.grid_row.clone();
+        self.grid_column = from.grid_column.clone();
+      },
+      I::JustifyItems => self.justify_items = from.justify_items,
+      I::JustifySelf => self.justify_self = from.justify_self,
+      I::PlaceItems => {
+        self.align_items = from.align_items;
+        self.justify_items = from.justify_items;
+      },
+      I::PlaceSelf => {
+        self.align_self = from.align_self;
+        self.justify_self = from.justify_self;
+      },
+      I::PaddingTop => self.padding.top = from.padding.top,
+      I::PaddingBottom => self.padding.bottom = from.padding.bottom,
+      I::PaddingRight => self.padding.right = from.padding.right,
+      I::PaddingLeft => self.padding.left = from.padding.left,
+      I::Padding => self.padding = from.padding.clone(),
+      I::MarginTop => self.margin.top = from.margin.top,
+      I::MarginBottom => self.margin.bottom = from.margin.bottom,
+      I::MarginRight => self.margin.right = from.margin.right,
+      I::MarginLeft => self.margin.left = from.margin.left,
+      I::Margin => self.margin = from.margin.clone(),
+      I::BorderTopColor => self.border.top.color = from.border.top.color,
+      I::BorderBottomColor => self.border.bottom.color = from.border.bottom.color,
+      I::BorderLeftColor => self.border.left.color = from.border.left.color,
+      I::BorderRightColor => self.border.right.color = from.border.right.color,
+      I::BorderTopWidth => self.border.top.width = from.border.top.width,
+      I::BorderBottomWidth => self.border.bottom.width = from.border.bottom.width,
+      I::BorderLeftWidth => self.border.left.width = from.border.left.width,
+      I::BorderRightWidth => self.border.right.width = from.border.right.width,
+      I::BorderWidth => {
+        self.border.top.width = from.border.top.width;
+        self.border.bottom.width = from.border.bottom.width;
+        self.border.left.width = from.border.left.width;
+        self.border.right.width = from.border.right.width;
+      },
+      I::BorderColor => {
+        self.border.top.color = from.border.top.color;
+        self.border.bottom.color = from.border.bottom.color;
+        self.border.left.color = from.border.left.color;
+        self.border.right.color = from.border.right.color;
+      },
+      I::BorderTopStyle => self.border_style.top = from.border_style.top,
+      I::BorderBottomStyle => self.border_style.bottom = from.border_style.bottom,
+      I::BorderLeftStyle => self.border_style.left = from.border_style.left,
+      I::BorderRightStyle => self.border_style.right = from.border_style.right,
+      I::BorderStyle => self.border_style = from.border_style.clone(),
+      I::BorderTop => {
+        self.border.top = from.border.top;
+        self.border_style.top = from.border_style.top;
+      },
+      I::BorderBottom => {
+        self.border.bottom = from.border.bottom;
+        self.border_style.bottom = from.border_style.bottom;
+      },
+      I::BorderLeft => {
+        self.border.left = from.border.left;
+        self.border_style.left = from.border_style.left;
+      },
+      I::BorderRight => {
+        self.border.right = from.border.right;
+        self.border_style.right = from.border_style.right;
+      },
+      I::Border => {
+        self.border = from.border.clone();
+        self.border_style = from.border_style.clone();
+      },
+      I::OutlineWidth => self.outline.width = from.outline.width,
+      I::OutlineStyle => self.outline.style = from.outline.style,
+      I::OutlineColor => self.outline.color = from.outline.color,
+      I::Outline => {
+        self.outline.width = from.outline.width;
+        self.outline.style = from.outline.style;
+        self.outline.color = from.outline.color;
+      },
+      I::Color => self.color = from.color,
+      I::BackgroundColor => self.background_color = from.background_color,
+      I::BackgroundImage => self.background_image = from.background_image.clone(),
+      I::BackgroundPosition | I::BackgroundPositionX | I::BackgroundPositionY => self.background_position = from.background_position.clone(),
+      I::BackgroundSize => self.background_size = from.background_size.clone(),
+      I::BackgroundRepeat => self.background_repeat = from.background_repeat.clone(),
+      I::Background => {
+        self.background_color = from.background_color;
+        self.background_image = from.background_image.clone();
+        self.background_position = from.background_position.clone();
+        self.background_size = from.background_size.clone();
+        self.background_repeat = from.background_repeat.clone();
+      },
+      I::BorderTopLeftRadius(_) => self.border_radius.nw = from.border_radius.nw,
+      I::BorderTopRightRadius(_) => self.border_radius.ne = from.border_radius.ne,
+      I::BorderBottomLeftRadius(_) => self.border_radius.sw = from.border_radius.sw,
+      I::BorderBottomRightRadius(_) => self.border_radius.se = from.border_radius.se,
+      I::BorderRadius(_) => self.border_radius = from.border_radius.clone(),
+      I::Custom(name) if name.as_ref() == "text-wrap" => self.text_wrap = from.text_wrap,
+      I::Custom(name) if name.as_ref() == "pointer-events" => self.pointer_events = from.pointer_events,
+      I::Custom(name) if name.as_ref() == "outline-offset" => self.outline.offset = from.outline.offset,
+      I::Custom(name) if name.as_ref() == "content" => self.content = from.content.clone(),
+      _ => bail!("Unsupported property: {}", id.name()),
+    }
+    Ok(())
+  }
+
+  /// `base` is the directory `url()`s are relative to, `colors` the system
+  /// colors of the theme. The `color` property must be applied first, for
+  /// `currentColor`.
+  pub(crate) fn apply(&mut self, p: &Property<'_>, context: &Context, parent: &ComputedProperties, base: Option<&Path>, colors: &SystemColors) -> Result<()> {
+    use lightningcss::properties::align::{Gap, GapValue, JustifySelf, PlaceContent, PlaceItems, PlaceSelf};
+    use lightningcss::properties::animation::{
+      AnimationDirection as CssAnimationDirection, AnimationFillMode, AnimationIterationCount, AnimationName as CssAnimationName, AnimationPlayState,
+    };
+    use lightningcss::properties::background::BackgroundSize as CssBackgroundSize;
+    use lightningcss::properties::border::{BorderColor, BorderSideWidth, BorderStyle as BorderStyles, BorderWidth, GenericBorder};
     use lightningcss::properties::border_radius::BorderRadius;
-    use lightningcss::properties::flex::FlexDirection;
-    use lightningcss::properties::font::{FontSize, VerticalAlign, VerticalAlignKeyword};
+    use lightningcss::properties::display::Visibility as CssVisibility;
+    use lightningcss::properties::flex::{Flex, FlexFlow};
+    use lightningcss::properties::font::{FontSize, FontStyle as CssFontStyle, LineHeight as CssLineHeight, VerticalAlign, VerticalAlignKeyword};
+    use lightningcss::properties::grid;
     use lightningcss::properties::margin_padding::{Margin, Padding};
+    use lightningcss::properties::outline::OutlineStyle;
+    use lightningcss::properties::overflow::TextOverflow as CssTextOverflow;
     use lightningcss::properties::size::{MaxSize, Size};
-    use lightningcss::properties::text::TextAlign;
-    use lightningcss::values::length::LengthValue::Px;
-    use lightningcss::values::length::{Length, LengthPercentageOrAuto};
-    use lightningcss::values::percentage::DimensionPercentage::Dimension;
-    use lightningcss::values::size::Size2D;
+    use lightningcss::properties::text::{
+      Spacing, TextAlign, TextDecorationThickness, TextJustify as CssTextJustify, TextTransformCase, WhiteSpace as CssWhiteSpace, WordBreak as CssWordBreak,
+    };
+    use lightningcss::traits::Parse;
+    use lightningcss::values::alpha::AlphaValue;
+    use lightningcss::values::gradient::{Circle, Ellipse, EndingShape, Gradient as CssGradient, GradientItem};
+    use lightningcss::values::ident::CustomIdentList;
+    use lightningcss::values::image::Image as CssImage;
+    use lightningcss::values::length::{Length as CssLength, LengthPercentageOrAuto};
+    use lightningcss::values::position::{HorizontalPosition, VerticalPosition};
+    use lightningcss::values::time::Time;
     use Property as P;
 
+    let parent_font_size = parent.font_size.unwrap_or(context.root_font_size);
+    let lengths = Lengths {
+      context,
+      font_size: self.font_size.unwrap_or(parent_font_size),
+    };
+    // Percentages of width, height, margin and padding are relative to the
+    // containing block. Percentages of border-radius, relative to the
+    // element's own box, are kept as percentages.
+    let cb_width = context.containing_block.map(|(w, _)| w);
+    let cb_height = context.containing_block.map(|(_, h)| h);
+    let main_size = if parent.direction.is_horizontal() { cb_width } else { cb_height };
+    let unresolved = || {
+      let o = PrinterOptions::default();
+      anyhow!("Could not resolve length: {}", p.to_css_string(false, o).unwrap_or_default())
+    };
+    let px = |v: Option<f32>| v.ok_or_else(unresolved);
+    let current = self.color;
+    let color = |c: &CssColor| resolve_color(c, current, colors);
+    // `None` for `currentColor`, which is resolved when used, as it is
+    // inherited as a keyword.
+    let color_or_current = |c: &CssColor| {
+      match c {
+        CssColor::CurrentColor => None,
+        c => Some(color(c)),
+      }
+    };
+    let length = |l: &LengthPercentage, basis: Option<f32>| lengths.length_percentage(l, basis).ok_or_else(unresolved);
+    let side = |v: &LengthPercentageOrAuto| {
+      match v {
+        LengthPercentageOrAuto::LengthPercentage(l) => length(l, cb_width),
+        LengthPercentageOrAuto::Auto => Ok(Length::Auto),
+      }
+    };
+    let size = |v: &Size, basis: Option<f32>| {
+      match v {
+        Size::Auto => Ok(Length::Auto),
+        Size::LengthPercentage(l) => length(l, basis),
+        Size::MinContent(_) => Ok(Length::MinContent),
+        Size::MaxContent(_) => Ok(Length::MaxContent),
+        Size::FitContent(_) => Ok(Length::FitContent),
+        _ => bail!("Unsupported size"),
+      }
+    };
+    let max_size = |v: &MaxSize, basis: Option<f32>| {
+      match v {
+        MaxSize::None => Ok(None),
+        MaxSize::LengthPercentage(l) => length(l, basis).map(Some),
+        MaxSize::MinContent(_) => Ok(Some(Length::MinContent)),
+        MaxSize::MaxContent(_) => Ok(Some(Length::MaxContent)),
+        MaxSize::FitContent(_) => Ok(Some(Length::FitContent)),
+        _ => bail!("Unsupported size"),
+      }
+    };
+    let gap = |v: &GapValue, basis: Option<f32>| {
+      match v {
+        GapValue::Normal => Ok(Length::default()),
+        GapValue::LengthPercentage(l) => length(l, basis),
+      }
+    };
+    let breadth = |b: &grid::TrackBreadth, basis: Option<f32>| -> Result<TrackBreadth> {
+      Ok(match b {
+        grid::TrackBreadth::Length(l) => TrackBreadth::Length(length(l, basis)?),
+        grid::TrackBreadth::Flex(f) => TrackBreadth::Fraction(*f),
+        grid::TrackBreadth::MinContent => TrackBreadth::MinContent,
+        grid::TrackBreadth::MaxContent => TrackBreadth::MaxContent,
+        grid::TrackBreadth::Auto => TrackBreadth::Auto,
+      })
+    };
+    let track_size = |t: &grid::TrackSize, basis: Option<f32>| -> Result<TrackSize> {
+      Ok(match t {
+        grid::TrackSize::TrackBreadth(b) => TrackSize::Breadth(breadth(b, basis)?),
+        grid::TrackSize::MinMax { min, max } => TrackSize::MinMax(breadth(min, basis)?, breadth(max, basis)?),
+        grid::TrackSize::FitContent(l) => TrackSize::FitContent(length(l, basis)?),
+      })
+    };
+    let track_sizes = |sizes: &[grid::TrackSize], basis: Option<f32>| sizes.iter().map(|t| track_size(t, basis)).collect::<Result<Vec<_>>>();
+    let line_names =
+      |names: &[CustomIdentList<'_>]| -> Vec<Vec<String>> { names.iter().map(|names| names.iter().map(|n| n.0.to_string()).collect()).collect() };
+    let track_list = |t: &grid::TrackSizing<'_>, basis: Option<f32>| -> Result<TrackList> {
+      let list = match t {
+        grid::TrackSizing::None => return Ok(TrackList::default()),
+        grid::TrackSizing::TrackList(list) => list,
+      };
+      let tracks = list.items.iter().map(|item| {
+        Ok(match item {
+          grid::TrackListItem::TrackSize(t) => Track::Size(track_size(t, basis)?),
+          grid::TrackListItem::TrackRepeat(r) => {
+            Track::Repeat {
+              count: match r.count {
+                grid::RepeatCount::Number(n) => RepeatCount::Count(n.max(1) as u32),
+                grid::RepeatCount::AutoFill => RepeatCount::AutoFill,
+                grid::RepeatCount::AutoFit => RepeatCount::AutoFit,
+              },
+              line_names: line_names(&r.line_names),
+              sizes: track_sizes(&r.track_sizes, basis)?,
+            }
+          },
+        })
+      });
+      Ok(TrackList {
+        line_names: line_names(&list.line_names),
+        tracks: tracks.collect::<Result<_>>()?,
+      })
+    };
+    let areas = |a: &grid::GridTemplateAreas| {
+      match a {
+        grid::GridTemplateAreas::None => None,
+        grid::GridTemplateAreas::Areas { columns, areas } => {
+          Some(GridAreas {
+            columns: *columns,
+            areas: areas.clone(),
+          })
+        },
+      }
+    };
+    // Percentages are relative to the font size.
+    let thickness = |t: &TextDecorationThickness| {
+      match t {
+        TextDecorationThickness::LengthPercentage(l) => length(l, Some(lengths.font_size)).map(Some),
+        TextDecorationThickness::Auto | TextDecorationThickness::FromFont => Ok(None),
+      }
+    };
+    // Percentages of background positions and sizes are relative to the
+    // box, minus the image for positions. They are kept as percentages.
+    let layer_length = |l: &LengthPercentage| length(l, None);
+    let position_x = |x: &HorizontalPosition| position_component(x, |s| *s == HorizontalPositionKeyword::Right, &layer_length);
+    let position_y = |y: &VerticalPosition| position_component(y, |s| *s == VerticalPositionKeyword::Bottom, &layer_length);
+    let position = |x: &HorizontalPosition, y: &VerticalPosition| -> Result<Position> {
+      Ok(Position {
+        x: position_x(x)?,
+        y: position_y(y)?,
+      })
+    };
+    let gradient = |g: &CssGradient| -> Result<Gradient> {
+      let (shape, items) = match g {
+        CssGradient::Linear(l) | CssGradient::RepeatingLinear(l) => (GradientShape::Linear(line_angle(&l.direction)), &l.items),
+        CssGradient::Radial(r) | CssGradient::RepeatingRadial(r) => {
+          let shape = match &r.shape {
+            EndingShape::Circle(Circle::Radius(l)) => RadialShape::Circle(px(lengths.length(l))?),
+            EndingShape::Circle(Circle::Extent(e)) => {
+              RadialShape::Extent {
+                circle: true,
+                extent: e.into(),
+              }
+            },
+            EndingShape::Ellipse(Ellipse::Size { x, y }) => RadialShape::Ellipse(layer_length(x)?, layer_length(y)?),
+            EndingShape::Ellipse(Ellipse::Extent(e)) => {
+              RadialShape::Extent {
+                circle: false,
+                extent: e.into(),
+              }
+            },
+          };
+          let center = position(&r.position.x, &r.position.y)?;
+          (GradientShape::Radial { shape, center }, &r.items)
+        },
+        _ => bail!("Unsupported gradient"),
+      };
+      // Transition hints are ignored: colors are interpolated linearly.
+      let stops = items.iter().filter_map(|item| {
+        match item {
+          GradientItem::ColorStop(stop) => Some(stop),
+          GradientItem::Hint(_) => None,
+        }
+      });
+      let stops = stops.map(|stop| {
+        Ok(ColorStop {
+          color: color(&stop.color),
+          position: stop.position.as_ref().map(layer_length).transpose()?,
+        })
+      });
+      Ok(Gradient {
+        shape,
+        stops: stops.collect::<Result<_>>()?,
+        repeating: matches!(g, CssGradient::RepeatingLinear(_) | CssGradient::RepeatingRadial(_)),
+      })
+    };
+    let image = |i: &CssImage<'_>| -> Result<Option<Image>> {
+      Ok(match i {
+        CssImage::None => None,
+        CssImage::Url(url) => Some(Image::Url(resolve_url(url.url.as_ref(), base))),
+        CssImage::Gradient(g) => Some(Image::Gradient(gradient(g)?)),
+        CssImage::ImageSet(_) => bail!("Unsupported image-set()"),
+      })
+    };
+    let background_size = |s: &CssBackgroundSize| -> Result<BackgroundSize> {
+      let side = |v: &LengthPercentageOrAuto| {
+        match v {
+          LengthPercentageOrAuto::LengthPercentage(l) => layer_length(l),
+          LengthPercentageOrAuto::Auto => Ok(Length::Auto),
+        }
+      };
+      Ok(match s {
+        CssBackgroundSize::Explicit { width, height } => BackgroundSize::Explicit(side(width)?, side(height)?),
+        CssBackgroundSize::Cover => BackgroundSize::Cover,
+        CssBackgroundSize::Contain => BackgroundSize::Contain,
+      })
+    };
+    let border_width = |v: &BorderSideWidth| {
+      match v {
+        BorderSideWidth::Thin => Ok(context.snap(1.0)),
+        BorderSideWidth::Medium => Ok(context.snap(MEDIUM_WIDTH)),
+        BorderSideWidth::Thick => Ok(context.snap(5.0)),
+        BorderSideWidth::Length(l) => px(lengths.length(l)).map(|w| context.snap(w)),
+      }
+    };
+    let border_side = |b: &GenericBorder<LineStyle, _>| -> Result<(WidthAndColor, BorderStyle)> {
+      let side = WidthAndColor {
+        width: border_width(&b.width)?,
+        color: color(&b.color),
+      };
+      Ok((side, (&b.style).into()))
+    };
+    let outline_style = |s: &OutlineStyle| {
+      match s {
+        OutlineStyle::Auto => None,
+        OutlineStyle::LineStyle(s) => Some(s.into()),
+      }
+    };
+    let seconds = |t: &Time| t.to_ms() / 1000.0;
+    let transition_property = |id: &PropertyId<'_>| {
+      match id {
+        PropertyId::All => Some(TransitionProperty::All),
+        // `transition-property: none`
+        PropertyId::Custom(name) if name.as_ref() == "none" => None,
+        id => Some(TransitionProperty::Property(id.name().to_owned())),
+      }
+    };
+    let animation_name = |name: &CssAnimationName<'_>| {
+      match name {
+        CssAnimationName::None => None,
+        CssAnimationName::Ident(ident) => Some(ident.0.to_string()),
+        CssAnimationName::String(s) => Some(s.to_string()),
+      }
+    };
+    let iteration_count = |count: &AnimationIterationCount| {
+      match count {
+        AnimationIterationCount::Number(n) => IterationCount::Count(n.max(0.0)),
+        AnimationIterationCount::Infinite => IterationCount::Infinite,
+      }
+    };
+    let animation_direction = |direction: &CssAnimationDirection| {
+      match direction {
+        CssAnimationDirection::Normal => AnimationDirection::Normal,
+        CssAnimationDirection::Reverse => AnimationDirection::Reverse,
+        CssAnimationDirection::Alternate => AnimationDirection::Alternate,
+        CssAnimationDirection::AlternateReverse => AnimationDirection::AlternateReverse,
+      }
+    };
+    let fill_mode = |mode: &AnimationFillMode| {
+      match mode {
+        AnimationFillMode::None => FillMode::None,
+        AnimationFillMode::Forwards => FillMode::Forwards,
+        AnimationFillMode::Backwards => FillMode::Backwards,
+        AnimationFillMode::Both => FillMode::Both,
+      }
+    };
+    let play_state = |state: &AnimationPlayState| {
+      match state {
+        AnimationPlayState::Running => PlayState::Running,
+        AnimationPlayState::Paused => PlayState::Paused,
+      }
+    };
+    // Shadows with lengths relative to an unknown size are dropped, the
+    // others are kept.
+    let shadow = |offsets: [&CssLength; 4], c: &CssColor, inset: bool| {
+      match offsets.map(|l| lengths.length(l)) {
+        [Some(x), Some(y), Some(blur), Some(spread)] => {
+          Some(Shadow {
+            x,
+            y,
+            blur,
+            spread,
+            color: color_or_current(c),
+            inset,
+          })
+        },
+        _ => {
+          warn!("{}", unresolved());
+          None
+        },
+      }
+    };
+    let radius = |Size2D(a, b): &Size2D<LengthPercentage>| -> Result<(Length, Length)> { Ok((length(a, None)?, length(b, None)?)) };
+
     match p {
-      P::FontSize(FontSize::Length(Dimension(Px(v)))) => self.font_size = Some(*v),
+      P::FontSize(FontSize::Length(l)) => {
+        let font_size = Lengths {
+          context,
+          font_size: parent_font_size,
+        };
+        match font_size.length_percentage(l, Some(parent_font_size)) {
+          Some(Length::Px(v)) => self.font_size = Some(v),
+          _ => return Err(unresolved()),
+        }
+      },
+      P::FontFamily(families) => self.font_family = families.iter().filter_map(FontFamily::from_css).collect(),
+      P::FontWeight(w) => self.font_weight = font_weight(w, parent.font_weight),
+      P::FontStyle(CssFontStyle::Normal) => self.font_style = FontStyle::Normal,
+      P::FontStyle(CssFontStyle::Italic) => self.font_style = FontStyle::Italic,
+      P::FontStyle(CssFontStyle::Oblique(a)) => self.font_style = FontStyle::Oblique(a.to_degrees()),
+      P::FontStretch(s) => self.font_stretch = font_stretch(s),
+      P::LineHeight(CssLineHeight::Normal) => self.line_height = LineHeight::Normal,
+      P::LineHeight(CssLineHeight::Number(n)) => self.line_height = LineHeight::Number(*n),
+      // Percentages are relative to the font size of the element, and
+      // inherited as a length, unlike numbers.
+      P::LineHeight(CssLineHeight::Length(l)) => self.line_height = LineHeight::Length(length(l, Some(lengths.font_size))?),
+      P::Font(f) => {
+        // The font size first, as the line height may be relative to it.
+        self.apply(&P::FontSize(f.size.clone()), context, parent, base, colors)?;
+        self.apply(&P::FontFamily(f.family.clone()), context, parent, base, colors)?;
+        self.apply(&P::FontStyle(f.style.clone()), context, parent, base, colors)?;
+        self.apply(&P::FontWeight(f.weight.clone()), context, parent, base, colors)?;
+        self.apply(&P::FontStretch(f.stretch.clone()), context, parent, base, colors)?;
+        self.apply(&P::LineHeight(f.line_height.clone()), context, parent, base, colors)?;
+      },
+      P::LetterSpacing(Spacing::Normal) => self.letter_spacing = Length::default(),
+      P::LetterSpacing(Spacing::Length(l)) => self.letter_spacing = lengths.typed_length(l).ok_or_else(unresolved)?,
 
       P::TextAlign(TextAlign::Start | TextAlign::Left) => self.text_align = Align::Start,
       P::TextAlign(TextAlign::End | TextAlign::Right) => self.text_align = Align::End,
       P::TextAlign(TextAlign::Center) => self.text_align = Align::Center,
       P::TextAlign(TextAlign::Justify) => self.text_align = Align::Justify,
 
+      P::BoxShadow(shadows, _) => {
+        self.box_shadow = shadows
+          .iter()
+          .filter_map(|s| shadow([&s.x_offset, &s.y_offset, &s.blur, &s.spread], &s.color, s.inset))
+          .collect()
+      },
+      P::TextShadow(shadows) => {
+        self.text_shadow = shadows
+          .iter()
+          .filter_map(|s| shadow([&s.x_offset, &s.y_offset, &s.blur, &s.spread], &s.color, false))
+          .collect()
+      },
+      P::TextDecorationLine(l, _) => self.text_decoration.set_line(l),
+      P::TextDecorationStyle(s, _) => self.text_decoration.style = s.into(),
+      P::TextDecorationColor(c, _) => self.text_decoration.color = color_or_current(c),
+      P::TextDecorationThickness(t) => self.text_decoration.thickness = thickness(t)?,
+      P::TextDecoration(d, _) => {
+        self.text_decoration.set_line(&d.line);
+        self.text_decoration.style = (&d.style).into();
+        self.text_decoration.color = color_or_current(&d.color);
+        self.text_decoration.thickness = thickness(&d.thickness)?;
+      },
+      P::TextTransform(t) => {
+        self.text_transform = match t.case {
+          TextTransformCase::None => TextTransform::None,
+          TextTransformCase::Uppercase => TextTransform::Uppercase,
+          TextTransformCase::Lowercase => TextTransform::Lowercase,
+          TextTransformCase::Capitalize => TextTransform::Capitalize,
+        }
+      },
+      P::TextOverflow(CssTextOverflow::Clip, _) => self.text_overflow = TextOverflow::Clip,
+      P::TextOverflow(CssTextOverflow::Ellipsis, _) => self.text_overflow = TextOverflow::Ellipsis,
+      P::WhiteSpace(w) => {
+        self.white_space = match w {
+          CssWhiteSpace::Normal => WhiteSpace::Normal,
+          CssWhiteSpace::Pre => WhiteSpace::Pre,
+          CssWhiteSpace::NoWrap => WhiteSpace::NoWrap,
+          CssWhiteSpace::PreWrap => WhiteSpace::PreWrap,
+          CssWhiteSpace::BreakSpaces => WhiteSpace::BreakSpaces,
+          CssWhiteSpace::PreLine => WhiteSpace::PreLine,
+        }
+      },
+      // Not known to the parser.
+      P::Custom(CustomProperty { name, value }) if name.as_ref() == "text-wrap" => {
+        self.text_wrap = match single_ident(value) {
+          Some("wrap") => TextWrap::Wrap,
+          Some("nowrap") => TextWrap::NoWrap,
+          Some("balance") => TextWrap::Balance,
+          Some("pretty") => TextWrap::Pretty,
+          Some("stable") => TextWrap::Stable,
+          _ => bail!("Unsupported text-wrap value"),
+        }
+      },
+      P::WordBreak(w) => {
+        self.word_break = match w {
+          CssWordBreak::Normal => WordBreak::Normal,
+          CssWordBreak::KeepAll => WordBreak::KeepAll,
+          CssWordBreak::BreakAll => WordBreak::BreakAll,
+          CssWordBreak::BreakWord => WordBreak::BreakWord,
+        }
+      },
+      P::TextIndent(i) => {
+        self.text_indent = TextIndent {
+          length: length(&i.value, cb_width)?,
+          hanging: i.hanging,
+          each_line: i.each_line,
+        }
+      },
+      P::TextJustify(j) => {
+        self.text_justify = match j {
+          CssTextJustify::Auto => TextJustify::Auto,
+          CssTextJustify::None => TextJustify::None,
+          CssTextJustify::InterWord => TextJustify::InterWord,
+          CssTextJustify::InterCharacter => TextJustify::InterCharacter,
+        }
+      },
+
       P::VerticalAlign(VerticalAlign::Keyword(VerticalAlignKeyword::Top)) => self.vertical_align = Align::Start,
       P::VerticalAlign(VerticalAlign::Keyword(VerticalAlignKeyword::Middle)) => self.vertical_align = Align::Center,
       P::VerticalAlign(VerticalAlign::Keyword(VerticalAlignKeyword::Bottom)) => self.vertical_align = Align::End,
 
-      P::Width(Size::LengthPercentage(Dimension(Px(v)))) => self.width = Some(*v),
-      P::Height(Size::LengthPercentage(Dimension(Px(v)))) => self.height = Some(*v),
-      P::MinWidth(Size::LengthPercentage(Dimension(Px(v)))) => self.min_width = Some(*v),
-      P::MinHeight(Size::LengthPercentage(Dimension(Px(v)))) => self.min_height = Some(*v),
-      P::MaxWidth(MaxSize::LengthPercentage(Dimension(Px(v)))) => self.max_width = Some(*v),
-      P::MaxHeight(MaxSize::LengthPercentage(Dimension(Px(v)))) => self.max_height = Some(*v),
-      P::FlexDirection(FlexDirection::Row, _) => self.direction = Direction::Horizontal,
-      P::FlexDirection(FlexDirection::Column, _) => self.direction = Direction::Vertical,
+      P::Width(v) => self.width = size(v, cb_width)?,
+      P::Height(v) => self.height = size(v, cb_height)?,
+      P::MinWidth(v) => self.min_width = size(v, cb_width)?,
+      P::MinHeight(v) => self.min_height = size(v, cb_height)?,
+      P::MaxWidth(v) => self.max_width = max_size(v, cb_width)?,
+      P::MaxHeight(v) => self.max_height = max_size(v, cb_height)?,
+      P::FlexDirection(d, _) => self.direction = d.into(),
+      P::FlexWrap(w, _) => self.wrap = w.into(),
+      P::FlexFlow(FlexFlow { direction, wrap }, _) => {
+        self.direction = direction.into();
+        self.wrap = wrap.into();
+      },
       P::FlexGrow(v, _) => self.flex_grow = *v,
-      P::FlexBasis(LengthPercentageOrAuto::Auto, _) => self.flex_basis = None,
-      P::FlexBasis(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v))), _) => self.flex_basis = Some(*v),
-      P::PaddingTop(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.padding.top = *v,
-      P::PaddingBottom(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.padding.bottom = *v,
-      P::PaddingRight(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.padding.right = *v,
-      P::PaddingLeft(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.padding.left = *v,
-      P::Padding(Padding {
-        top: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(t))),
-        bottom: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(b))),
-        right: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(r))),
-        left: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(l))),
-      }) => {
-        self.padding.top = *t;
-        self.padding.bottom = *b;
-        self.padding.left = *l;
-        self.padding.right = *r;
-      },
-      P::MarginTop(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.margin.top = *v,
-      P::MarginBottom(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.margin.bottom = *v,
-      P::MarginRight(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.margin.right = *v,
-      P::MarginLeft(LengthPercentageOrAuto::LengthPercentage(Dimension(Px(v)))) => self.margin.left = *v,
-      P::Margin(Margin {
-        top: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(t))),
-        bottom: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(b))),
-        right: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(r))),
-        left: LengthPercentageOrAuto::LengthPercentage(Dimension(Px(l))),
-      }) => {
-        self.margin.top = *t;
-        self.margin.bottom = *b;
-        self.margin.left = *l;
-        self.margin.right = *r;
-      },
-      P::BorderTopColor(c) => self.border.top.color = c.into(),
-      P::BorderBottomColor(c) => self.border.bottom.color = c.into(),
-      P::BorderLeftColor(c) => self.border.left.color = c.into(),
-      P::BorderRightColor(c) => self.border.right.color = c.into(),
-      P::BorderTopWidth(BorderSideWidth::Length(Length::Value(Px(v)))) => self.border.top.width = *v,
-      P::BorderBottomWidth(BorderSideWidth::Length(Length::Value(Px(v)))) => self.border.bottom.width = *v,
-      P::BorderLeftWidth(BorderSideWidth::Length(Length::Value(Px(v)))) => self.border.left.width = *v,
-      P::BorderRightWidth(BorderSideWidth::Length(Length::Value(Px(v)))) => self.border.right.width = *v,
-      P::BorderWidth(BorderWidth {
-        top: BorderSideWidth::Length(Length::Value(Px(t))),
-        bottom: BorderSideWidth::Length(Length::Value(Px(b))),
-        right: BorderSideWidth::Length(Length::Value(Px(r))),
-        left: BorderSideWidth::Length(Length::Value(Px(l))),
+      P::FlexShrink(v, _) => self.flex_shrink = *v,
+      P::FlexBasis(LengthPercentageOrAuto::Auto, _) => self.flex_basis = Length::Auto,
+      P::FlexBasis(LengthPercentageOrAuto::LengthPercentage(l), _) => self.flex_basis = length(l, main_size)?,
+      P::Flex(Flex { grow, shrink, basis }, _) => {
+        self.flex_grow = *grow;
+        self.flex_shrink = *shrink;
+        self.flex_basis = match basis {
+          LengthPercentageOrAuto::Auto => Length::Auto,
+          LengthPercentageOrAuto::LengthPercentage(l) => length(l, main_size)?,
+        };
+      },
+      P::Order(v, _) => self.order = *v,
+      P::JustifyContent(v, _) => self.justify_content = v.into(),
+      P::AlignContent(v, _) => self.align_content = v.into(),
+      P::PlaceContent(PlaceContent { align, justify }) => {
+        self.align_content = align.into();
+        self.justify_content = justify.into();
+      },
+      P::AlignItems(v, _) => self.align_items = v.into(),
+      P::AlignSelf(AlignSelf::Auto, _) => self.align_self = None,
+      P::AlignSelf(v, _) => self.align_self = Some(v.into()),
+      P::JustifyItems(v) => self.justify_items = v.into(),
+      P::JustifySelf(JustifySelf::Auto) => self.justify_self = None,
+      P::JustifySelf(v) => self.justify_self = Some(v.into()),
+      P::PlaceItems(PlaceItems { align, justify }) => {
+        self.align_items = align.into();
+        self.justify_items = justify.into();
+      },
+      P::PlaceSelf(PlaceSelf { align, justify }) => {
+        self.align_self = match align {
+          AlignSelf::Auto => None,
+          align => Some(align.into()),
+        };
+        self.justify_self = match justify {
+          JustifySelf::Auto => None,
+          justify => Some(justify.into()),
+        };
+      },
+      P::Display(d) => self.display = d.into(),
+      P::Opacity(AlphaValue(o)) => self.opacity = o.clamp(0.0, 1.0),
+      P::Visibility(v) => {
+        self.visibility = match v {
+          CssVisibility::Visible => Visibility::Visible,
+          CssVisibility::Hidden => Visibility::Hidden,
+          CssVisibility::Collapse => Visibility::Collapse,
+        }
+      },
+      P::Cursor(c) => self.cursor = Cursor::new(c, base),
+      P::TransitionProperty(ids, _) => self.transition_property = ids.iter().filter_map(transition_property).collect(),
+      P::TransitionDuration(times, _) => self.transition_duration = times.iter().map(seconds).collect(),
+      P::TransitionTimingFunction(functions, _) => self.transition_timing_function = functions.iter().map(TimingFunction::from).collect(),
+      P::TransitionDelay(times, _) => self.transition_delay = times.iter().map(seconds).collect(),
+      P::Transition(transitions, _) => {
+        self.transition_property = transitions.iter().filter_map(|t| transition_property(&t.property)).collect();
+        self.transition_duration = transitions.iter().map(|t| seconds(&t.duration)).collect();
+        self.transition_timing_function = transitions.iter().map(|t| (&t.timing_function).into()).collect();
+        self.transition_delay = transitions.iter().map(|t| seconds(&t.delay)).collect();
+      },
+      P::AnimationName(names, _) => self.animation_name = names.iter().map(animation_name).collect(),
+      P::AnimationDuration(times, _) => self.animation_duration = times.iter().map(seconds).collect(),
+      P::AnimationTimingFunction(functions, _) => self.animation_timing_function = functions.iter().map(TimingFunction::from).collect(),
+      P::AnimationIterationCount(counts, _) => self.animation_iteration_count = counts.iter().map(iteration_count).collect(),
+      P::AnimationDirection(directions, _) => self.animation_direction = directions.iter().map(animation_direction).collect(),
+      P::AnimationFillMode(modes, _) => self.animation_fill_mode = modes.iter().map(fill_mode).collect(),
+      P::AnimationPlayState(states, _) => self.animation_play_state = states.iter().map(play_state).collect(),
+      P::AnimationDelay(times, _) => self.animation_delay = times.iter().map(seconds).collect(),
+      P::Animation(animations, _) => {
+        self.animation_name = animations.iter().map(|a| animation_name(&a.name)).collect();
+        self.animation_duration = animations.iter().map(|a| seconds(&a.duration)).collect();
+        self.animation_timing_function = animations.iter().map(|a| (&a.timing_function).into()).collect();
+        self.animation_iteration_count = animations.iter().map(|a| iteration_count(&a.iteration_count)).collect();
+        self.animation_direction = animations.iter().map(|a| animation_direction(&a.direction)).collect();
+        self.animation_fill_mode = animations.iter().map(|a| fill_mode(&a.fill_mode)).collect();
+        self.animation_play_state = animations.iter().map(|a| play_state(&a.play_state)).collect();
+        self.animation_delay = animations.iter().map(|a| seconds(&a.delay)).collect();
+      },
+      // Not known to the parser.
+      P::Custom(CustomProperty { name, value }) if name.as_ref() == "pointer-events" => {
+        self.pointer_events = match single_ident(value) {
+          Some("auto") => PointerEvents::Auto,
+          Some("none") => PointerEvents::None,
+          _ => bail!("Unsupported pointer-events value"),
+        }
+      },
+      P::GridTemplateColumns(t) => self.grid_template_columns = track_list(t, cb_width)?,
+      P::GridTemplateRows(t) => self.grid_template_rows = track_list(t, cb_height)?,
+      P::GridTemplateAreas(a) => self.grid_template_areas = areas(a),
+      P::GridTemplate(grid::GridTemplate { rows, columns, areas: a }) => {
+        self.grid_template_columns = track_list(columns, cb_width)?;
+        self.grid_template_rows = track_list(rows, cb_height)?;
+        self.grid_template_areas = areas(a);
+      },
+      P::GridAutoColumns(l) => self.grid_auto_columns = track_sizes(&l.0, cb_width)?,
+      P::GridAutoRows(l) => self.grid_auto_rows = track_sizes(&l.0, cb_height)?,
+      P::GridAutoFlow(f) => self.grid_auto_flow = f.into(),
+      P::Grid(g) => {
+        self.grid_template_columns = track_list(&g.columns, cb_width)?;
+        self.grid_template_rows = track_list(&g.rows, cb_height)?;
+        self.grid_template_areas = areas(&g.areas);
+        self.grid_auto_columns = track_sizes(&g.auto_columns.0, cb_width)?;
+        self.grid_auto_rows = track_sizes(&g.auto_rows.0, cb_height)?;
+        self.grid_auto_flow = (&g.auto_flow).into();
+      },
+      P::GridRowStart(l) => self.grid_row.start = l.into(),
+      P::GridRowEnd(l) => self.grid_row.end = l.into(),
+      P::GridColumnStart(l) => self.grid_column.start = l.into(),
+      P::GridColumnEnd(l) => self.grid_column.end = l.into(),
+      P::GridRow(grid::GridRow { start, end }) => {
+        self.grid_row = GridPlacement {
+          start: start.into(),
+          end: end.into(),
+        }
+      },
+      P::GridColumn(grid::GridColumn { start, end }) => {
+        self.grid_column = GridPlacement {
+          start: start.into(),
+          end: end.into(),
+        }
+      },
+      P::GridArea(grid::GridArea {
+        row_start,
+        column_start,
+        row_end,
+        column_end,
       }) => {
-        self.border.top.width = *t;
-        self.border.bottom.width = *b;
-        self.border.left.width = *l;
-        self.border.right.width = *r;
+        self.grid_row = GridPlacement {
+          start: row_start.into(),
+          end: row_end.into(),
+        };
+        self.grid_column = GridPlacement {
+          start: column_start.into(),
+          end: column_end.into(),
+        };
+      },
+      P::RowGap(v) => self.row_gap = gap(v, cb_height)?,
+      P::ColumnGap(v) => self.column_gap = gap(v, cb_width)?,
+      P::Gap(Gap { row, column }) => {
+        self.row_gap = gap(row, cb_height)?;
+        self.column_gap = gap(column, cb_width)?;
+      },
+      P::PaddingTop(v) => self.padding.top = side(v)?,
+      P::PaddingBottom(v) => self.padding.bottom = side(v)?,
+      P::PaddingRight(v) => self.padding.right = side(v)?,
+      P::PaddingLeft(v) => self.padding.left = side(v)?,
+      P::Padding(Padding { top, bottom, right, left }) => {
+        self.padding = Sides {
+          top: side(top)?,
+          right: side(right)?,
+          bottom: side(bottom)?,
+          left: side(left)?,
+        };
+      },
+      P::MarginTop(v) => self.margin.top = side(v)?,
+      P::MarginBottom(v) => self.margin.bottom = side(v)?,
+      P::MarginRight(v) => self.margin.right = side(v)?,
+      P::MarginLeft(v) => self.margin.left = side(v)?,
+      P::Margin(Margin { top, bottom, right, left }) => {
+        self.margin = Sides {
+          top: side(top)?,
+          right: side(right)?,
+          bottom: side(bottom)?,
+          left: side(left)?,
+        };
+      },
+      P::BorderTopColor(c) => self.border.top.color = color(c),
+      P::BorderBottomColor(c) => self.border.bottom.color = color(c),
+      P::BorderLeftColor(c) => self.border.left.color = color(c),
+      P::BorderRightColor(c) => self.border.right.color = color(c),
+      P::BorderTopWidth(w) => self.border.top.width = border_width(w)?,
+      P::BorderBottomWidth(w) => self.border.bottom.width = border_width(w)?,
+      P::BorderLeftWidth(w) => self.border.left.width = border_width(w)?,
+      P::BorderRightWidth(w) => self.border.right.width = border_width(w)?,
+      P::BorderWidth(BorderWidth { top, bottom, right, left }) => {
+        self.border.top.width = border_width(top)?;
+        self.border.bottom.width = border_width(bottom)?;
+        self.border.left.width = border_width(left)?;
+        self.border.right.width = border_width(right)?;
       },
       P::BorderColor(BorderColor {
         top: t,
@@ -236,41 +2824,111 @@ impl ComputedProperties {
         right: r,
         left: l,
       }) => {
-        self.border.top.color = t.into();
-        self.border.bottom.color = b.into();
-        self.border.left.color = l.into();
-        self.border.right.color = r.into();
-      },
-      P::Border(GenericBorder {
-        style: _,
-        width: BorderSideWidth::Length(Length::Value(Px(w))),
-        color: c,
-      }) => {
-        let v = WidthAndColor { width: *w, color: c.into() };
+        self.border.top.color = color(t);
+        self.border.bottom.color = color(b);
+        self.border.left.color = color(l);
+        self.border.right.color = color(r);
+      },
+      P::BorderTopStyle(s) => self.border_style.top = s.into(),
+      P::BorderBottomStyle(s) => self.border_style.bottom = s.into(),
+      P::BorderLeftStyle(s) => self.border_style.left = s.into(),
+      P::BorderRightStyle(s) => self.border_style.right = s.into(),
+      P::BorderStyle(BorderStyles { top, bottom, right, left }) => {
+        self.border_style = Sides {
+          top: top.into(),
+          right: right.into(),
+          bottom: bottom.into(),
+          left: left.into(),
+        }
+      },
+      P::BorderTop(b) => (self.border.top, self.border_style.top) = border_side(b)?,
+      P::BorderBottom(b) => (self.border.bottom, self.border_style.bottom) = border_side(b)?,
+      P::BorderLeft(b) => (self.border.left, self.border_style.left) = border_side(b)?,
+      P::BorderRight(b) => (self.border.right, self.border_style.right) = border_side(b)?,
+      P::Border(b) => {
+        let (v, style) = border_side(b)?;
         self.border.top = v;
         self.border.bottom = v;
         self.border.left = v;
         self.border.right = v;
+        self.border_style = Sides {
+          top: style,
+          right: style,
+          bottom: style,
+          left: style,
+        };
+      },
+      P::OutlineWidth(w) => self.outline.width = border_width(w)?,
+      P::OutlineStyle(s) => self.outline.style = outline_style(s),
+      P::OutlineColor(c) => self.outline.color = color_or_current(c),
+      P::Outline(o) => {
+        self.outline.width = border_width(&o.width)?;
+        self.outline.style = outline_style(&o.style);
+        self.outline.color = color_or_current(&o.color);
+      },
+      // Not known to the parser.
+      P::Custom(CustomProperty { name, value }) if name.as_ref() == "content" => self.content = GeneratedContent::new(value, base)?,
+      P::Custom(CustomProperty { name, value }) if name.as_ref() == "outline-offset" => {
+        let offset = tokens_to_string(value).and_then(|source| CssLength::parse_string(&source).ok());
+        match offset {
+          Some(l) => self.outline.offset = px(lengths.length(&l))?,
+          None => bail!("Unsupported outline-offset value"),
+        }
       },
-      P::Color(c) => self.color = c.into(),
-      P::BackgroundColor(c) => self.background_color = c.into(),
-      P::BorderTopLeftRadius(Size2D(Dimension(Px(a)), Dimension(Px(b))), _) => self.border_radius.nw = (*a, *b),
-      P::BorderTopRightRadius(Size2D(Dimension(Px(a)), Dimension(Px(b))), _) => self.border_radius.ne = (*a, *b),
-      P::BorderBottomLeftRadius(Size2D(Dimension(Px(a)), Dimension(Px(b))), _) => self.border_radius.sw = (*a, *b),
-      P::BorderBottomRightRadius(Size2D(Dimension(Px(a)), Dimension(Px(b))), _) => self.border_radius.se = (*a, *b),
+      // `currentColor` is the inherited color.
+      P::Color(c) => self.color = resolve_color(c, parent.color, colors),
+      P::BackgroundColor(c) => self.background_color = color(c),
+      P::BackgroundImage(images) => self.background_image = images.iter().map(image).collect::<Result<_>>()?,
+      P::BackgroundPosition(positions) => self.background_position = positions.iter().map(|p| position(&p.x, &p.y)).collect::<Result<_>>()?,
+      P::BackgroundPositionX(xs) => {
+        let ys = &self.background_position;
+        let positions = xs.iter().enumerate().map(|(i, x)| {
+          Ok(Position {
+            x: position_x(x)?,
+            y: layer_value(ys, i).y,
+          })
+        });
+        self.background_position = positions.collect::<Result<_>>()?;
+      },
+      P::BackgroundPositionY(ys) => {
+        let xs = &self.background_position;
+        let positions = ys.iter().enumerate().map(|(i, y)| {
+          Ok(Position {
+            x: layer_value(xs, i).x,
+            y: position_y(y)?,
+          })
+        });
+        self.background_position = positions.collect::<Result<_>>()?;
+      },
+      P::BackgroundSize(sizes) => self.background_size = sizes.iter().map(background_size).collect::<Result<_>>()?,
+      P::BackgroundRepeat(repeats) => self.background_repeat = repeats.iter().map(|r| ((&r.x).into(), (&r.y).into())).collect(),
+      P::Background(layers) => {
+        self.background_image = layers.iter().map(|l| image(&l.image)).collect::<Result<_>>()?;
+        self.background_position = layers.iter().map(|l| position(&l.position.x, &l.position.y)).collect::<Result<_>>()?;
+        self.background_size = layers.iter().map(|l| background_size(&l.size)).collect::<Result<_>>()?;
+        self.background_repeat = layers.iter().map(|l| ((&l.repeat.x).into(), (&l.repeat.y).into())).collect();
+        // Only the bottom layer has a color.
+        if let Some(layer) = layers.last() {
+          self.background_color = color(&layer.color);
+        }
+      },
+      P::BorderTopLeftRadius(r, _) => self.border_radius.nw = radius(r)?,
+      P::BorderTopRightRadius(r, _) => self.border_radius.ne = radius(r)?,
+      P::BorderBottomLeftRadius(r, _) => self.border_radius.sw = radius(r)?,
+      P::BorderBottomRightRadius(r, _) => self.border_radius.se = radius(r)?,
       P::BorderRadius(
         BorderRadius {
-          top_left: Size2D(Dimension(Px(nwa)), Dimension(Px(nwb))),
-          top_right: Size2D(Dimension(Px(nea)), Dimension(Px(neb))),
-          bottom_left: Size2D(Dimension(Px(swa)), Dimension(Px(swb))),
-          bottom_right: Size2D(Dimension(Px(sea)), Dimension(Px(seb))),
+          top_left,
+          top_right,
+          bottom_left,
+          bottom_right,
         },
         _,
       ) => {
-        self.border_radius.nw = (*nwa, *nwb);
-        self.border_radius.ne = (*nea, *neb);
-        self.border_radius.sw = (*swa, *swb);
-        self.border_radius.se = (*sea, *seb);
+        self.border_radius.nw = radius(top_left)?;
+        self.border_radius.ne = radius(top_right)?;
+        self.border_radius.sw = radius(bottom_left)?;
+        self.border_radius.se = radius(bottom_right)?;
       },
       _ => {
         let o = PrinterOptions::default();
@@ -283,3 +2941,189 @@ impl ComputedProperties {
     Ok(())
   }
 }
+
+impl From<&FlexDirection> for Direction {
+  fn from(d: &FlexDirection) -> Self {
+    match d {
+      FlexDirection::Row => Direction::Horizontal,
+      FlexDirection::RowReverse => Direction::HorizontalReverse,
+      FlexDirection::Column => Direction::Vertical,
+      FlexDirection::ColumnReverse => Direction::VerticalReverse,
+    }
+  }
+}
+
+impl From<&FlexWrap> for Wrap {
+  fn from(w: &FlexWrap) -> Self {
+    match w {
+      FlexWrap::NoWrap => Wrap::NoWrap,
+      FlexWrap::Wrap => Wrap::Wrap,
+      FlexWrap::WrapReverse => Wrap::WrapReverse,
+    }
+  }
+}
+
+impl From<&ContentDistribution> for ContentAlign {
+  fn from(d: &ContentDistribution) -> Self {
+    match d {
+      ContentDistribution::SpaceBetween => ContentAlign::SpaceBetween,
+      ContentDistribution::SpaceAround => ContentAlign::SpaceAround,
+      ContentDistribution::SpaceEvenly => ContentAlign::SpaceEvenly,
+      ContentDistribution::Stretch => ContentAlign::Stretch,
+    }
+  }
+}
+
+impl From<&ContentPosition> for ContentAlign {
+  fn from(p: &ContentPosition) -> Self {
+    match p {
+      ContentPosition::Center => ContentAlign::Center,
+      ContentPosition::Start => ContentAlign::Start,
+      ContentPosition::End => ContentAlign::End,
+      ContentPosition::FlexStart => ContentAlign::FlexStart,
+      ContentPosition::FlexEnd => ContentAlign::FlexEnd,
+    }
+  }
+}
+
+impl From<&JustifyContent> for ContentAlign {
+  fn from(j: &JustifyContent) -> Self {
+    match j {
+      JustifyContent::Normal => ContentAlign::Normal,
+      JustifyContent::ContentDistribution(d) => d.into(),
+      JustifyContent::ContentPosition { value, .. } => value.into(),
+      JustifyContent::Left { .. } => ContentAlign::Start,
+      JustifyContent::Right { .. } => ContentAlign::End,
+    }
+  }
+}
+
+impl From<&AlignContent> for ContentAlign {
+  fn from(a: &AlignContent) -> Self {
+    match a {
+      AlignContent::Normal => ContentAlign::Normal,
+      // Baseline content alignment falls back to start.
+      AlignContent::BaselinePosition(_) => ContentAlign::Start,
+      AlignContent::ContentDistribution(d) => d.into(),
+      AlignContent::ContentPosition { value, .. } => value.into(),
+    }
+  }
+}
+
+impl From<&BaselinePosition> for ItemAlign {
+  fn from(b: &BaselinePosition) -> Self {
+    match b {
+      BaselinePosition::First => ItemAlign::Baseline,
+      BaselinePosition::Last => ItemAlign::LastBaseline,
+    }
+  }
+}
+
+impl From<&SelfPosition> for ItemAlign {
+  fn from(p: &SelfPosition) -> Self {
+    match p {
+      SelfPosition::Center => ItemAlign::Center,
+      SelfPosition::Start | SelfPosition::SelfStart => ItemAlign::Start,
+      SelfPosition::End | SelfPosition::SelfEnd => ItemAlign::End,
+      SelfPosition::FlexStart => ItemAlign::FlexStart,
+      SelfPosition::FlexEnd => ItemAlign::FlexEnd,
+    }
+  }
+}
+
+impl From<&AlignItems> for ItemAlign {
+  fn from(a: &AlignItems) -> Self {
+    match a {
+      AlignItems::Normal => ItemAlign::Normal,
+      AlignItems::Stretch => ItemAlign::Stretch,
+      AlignItems::BaselinePosition(b) => b.into(),
+      AlignItems::SelfPosition { value, .. } => value.into(),
+    }
+  }
+}
+
+/// `auto` is handled by the caller.
+impl From<&AlignSelf> for ItemAlign {
+  fn from(a: &AlignSelf) -> Self {
+    match a {
+      AlignSelf::Auto | AlignSelf::Normal => ItemAlign::Normal,
+      AlignSelf::Stretch => ItemAlign::Stretch,
+      AlignSelf::BaselinePosition(b) => b.into(),
+      AlignSelf::SelfPosition { value, .. } => value.into(),
+    }
+  }
+}
+
+impl From<&LegacyJustify> for ItemAlign {
+  fn from(l: &LegacyJustify) -> Self {
+    match l {
+      LegacyJustify::Left => ItemAlign::Start,
+      LegacyJustify::Right => ItemAlign::End,
+      LegacyJustify::Center => ItemAlign::Center,
+    }
+  }
+}
+
+impl From<&JustifyItems> for ItemAlign {
+  fn from(j: &JustifyItems) -> Self {
+    match j {
+      JustifyItems::Normal => ItemAlign::Normal,
+      JustifyItems::Stretch => ItemAlign::Stretch,
+      JustifyItems::BaselinePosition(b) => b.into(),
+      JustifyItems::SelfPosition { value, .. } => value.into(),
+      JustifyItems::Left { .. } => ItemAlign::Start,
+      JustifyItems::Right { .. } => ItemAlign::End,
+      JustifyItems::Legacy(l) => l.into(),
+    }
+  }
+}
+
+/// `auto` is handled by the caller.
+impl From<&JustifySelf> for ItemAlign {
+  fn from(j: &JustifySelf) -> Self {
+    match j {
+      JustifySelf::Auto | JustifySelf::Normal => ItemAlign::Normal,
+      JustifySelf::Stretch => ItemAlign::Stretch,
+      JustifySelf::BaselinePosition(b) => b.into(),
+      JustifySelf::SelfPosition { value, .. } => value.into(),
+      JustifySelf::Left { .. } => ItemAlign::Start,
+      JustifySelf::Right { .. } => ItemAlign::End,
+    }
+  }
+}
+
+impl From<&CssDisplay> for Display {
+  fn from(d: &CssDisplay) -> Self {
+    match d {
+      CssDisplay::Keyword(DisplayKeyword::None) => Display::None,
+      CssDisplay::Keyword(_) => Display::Flow,
+      CssDisplay::Pair(DisplayPair { inside, .. }) => {
+        match inside {
+          DisplayInside::Flex(_) | DisplayInside::Box(_) => Display::Flex,
+          DisplayInside::Grid => Display::Grid,
+          _ => Display::Flow,
+        }
+      },
+    }
+  }
+}
+
+impl From<&GridAutoFlow> for AutoFlow {
+  fn from(f: &GridAutoFlow) -> Self {
+    AutoFlow {
+      column: f.contains(GridAutoFlow::Column),
+      dense: f.contains(GridAutoFlow::Dense),
+    }
+  }
+}
+
+impl From<&CssGridLine<'_>> for GridLine {
+  fn from(l: &CssGridLine<'_>) -> Self {
+    match l {
+      CssGridLine::Auto => GridLine::Auto,
+      CssGridLine::Area { name } => GridLine::Area(name.0.to_string()),
+      CssGridLine::Line { index, name } => GridLine::Line(*index, name.as_ref().map(|n| n.0.to_string())),
+      CssGridLine::Span { index, name } => GridLine::Span(*index, name.as_ref().map(|n| n.0.to_string())),
+    }
+  }
+}
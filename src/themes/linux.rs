@@ -0,0 +1,100 @@
+//! Theme and accent color of the XDG Desktop Portal settings, as set by GNOME
+//! 47+ and KDE.
+//! See <https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html>
+use anyhow::Result;
+use crossbeam_channel::{never, unbounded, Receiver};
+use lazy_static::lazy_static;
+use lightningcss::values::color::SystemColor;
+use log::{error, warn};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::properties::Color;
+use crate::themes::{Event, SystemTheme};
+
+const APPEARANCE: &str = "org.freedesktop.appearance";
+
+lazy_static! {
+  static ref SETTINGS: Option<Proxy<'static>> = {
+    let proxy = Connection::session().and_then(|connection| {
+      Proxy::new(
+        &connection,
+        "org.freedesktop.portal.Desktop",
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.Settings",
+      )
+    });
+    match proxy {
+      Ok(proxy) => Some(proxy),
+      Err(e) => {
+        warn!("Desktop portal settings are not available: {e}");
+        None
+      },
+    }
+  };
+}
+
+fn read_setting(key: &str) -> Option<OwnedValue> {
+  SETTINGS.as_ref()?.call("ReadOne", &(APPEARANCE, key)).ok()
+}
+
+pub(crate) fn get_system_theme() -> SystemTheme {
+  // 0: no preference, 1: dark, 2: light.
+  match read_setting("color-scheme").and_then(|v| u32::try_from(v).ok()) {
+    Some(1) => SystemTheme::Dark,
+    _ => SystemTheme::Light,
+  }
+}
+
+/// Only the accent color is exposed by the portal.
+pub(crate) fn system_color(color: SystemColor) -> Option<Color> {
+  match color {
+    SystemColor::AccentColor | SystemColor::SelectedItem => {
+      let (r, g, b) = <(f64, f64, f64)>::try_from(Value::from(read_setting("accent-color")?)).ok()?;
+      // Out of range components mean the accent color is not set.
+      if [r, g, b].iter().any(|c| !(0.0..=1.0).contains(c)) {
+        return None;
+      }
+      Some(Color {
+        r: r as f32,
+        g: g as f32,
+        b: b as f32,
+        a: 1.0,
+      })
+    },
+    _ => None,
+  }
+}
+
+pub(crate) struct Watcher {
+  pub(crate) recv: Receiver<Event>,
+}
+
+/// Sends `Event::Changed` when system-wide theme or accent color changed.
+/// Without the portal, no event is ever sent.
+///
+/// # Errors
+///
+/// Will fail if the portal signals can't be subscribed to.
+pub(crate) fn watch() -> Result<Watcher> {
+  let settings = match SETTINGS.as_ref() {
+    Some(settings) => settings,
+    None => return Ok(Watcher { recv: never() }),
+  };
+  let (to_parent_thread, from_this_thread) = unbounded();
+  let signals = settings.receive_signal("SettingChanged")?;
+
+  std::thread::spawn(move || {
+    for signal in signals {
+      if !matches!(signal.body::<(String, String, OwnedValue)>(), Ok((namespace, ..)) if namespace == APPEARANCE) {
+        continue;
+      }
+      if let Err(e) = to_parent_thread.send(Event::Changed) {
+        error!("Sending message to css thread failed: {e}");
+        break;
+      }
+    }
+  });
+
+  Ok(Watcher { recv: from_this_thread })
+}
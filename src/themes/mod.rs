@@ -1,3 +1,7 @@
+use lightningcss::values::color::SystemColor;
+
+use crate::properties::Color;
+
 #[derive(Debug)]
 pub(crate) enum Event {
   Changed,
@@ -14,8 +18,139 @@ pub(crate) enum SystemTheme {
 #[path = "osx.rs"]
 mod platform;
 
+#[cfg(all(target_os = "linux", not(test)))]
+#[path = "linux.rs"]
+mod platform;
+
 #[cfg(test)]
 #[path = "test.rs"]
 mod platform;
 
 pub(crate) use platform::*;
+
+/// System colors of a theme, read from the platform once so that resolving
+/// them doesn't query it for every element.
+#[derive(Clone, Debug)]
+pub(crate) struct SystemColors {
+  pub(crate) theme: SystemTheme,
+  colors: Vec<(SystemColor, Color)>,
+}
+
+impl SystemColors {
+  pub(crate) fn new(theme: SystemTheme) -> Self {
+    use SystemColor::{
+      AccentColor, AccentColorText, ActiveText, ButtonBorder, ButtonFace, ButtonText, Canvas, CanvasText, Field, FieldText, GrayText, Highlight, HighlightText,
+      LinkText, Mark, MarkText, SelectedItem, SelectedItemText, VisitedText,
+    };
+    let modern = [
+      AccentColor,
+      AccentColorText,
+      ActiveText,
+      ButtonBorder,
+      ButtonFace,
+      ButtonText,
+      Canvas,
+      CanvasText,
+      Field,
+      FieldText,
+      GrayText,
+      Highlight,
+      HighlightText,
+      LinkText,
+      Mark,
+      MarkText,
+      SelectedItem,
+      SelectedItemText,
+      VisitedText,
+    ];
+    let colors = modern.into_iter().map(|color| (color, system_color(color, theme))).collect();
+    Self { theme, colors }
+  }
+
+  /// Value of a system color keyword.
+  pub(crate) fn get(&self, color: SystemColor) -> Color {
+    let color = modern_system_color(color);
+    self
+      .colors
+      .iter()
+      .find(|(c, _)| *c == color)
+      .map_or_else(|| default_system_color(color, self.theme), |(_, value)| *value)
+  }
+}
+
+/// Value of a system color keyword: the platform's, or a default for
+/// `theme`.
+/// See <https://drafts.csswg.org/css-color/#css-system-colors>
+fn system_color(color: SystemColor, theme: SystemTheme) -> Color {
+  use SystemColor::{AccentColor, AccentColorText, SelectedItemText};
+  let color = modern_system_color(color);
+  platform::system_color(color)
+    .or_else(|| {
+      // Platforms that only expose the accent color chosen by the user.
+      match color {
+        AccentColorText | SelectedItemText => platform::system_color(AccentColor).map(contrasting),
+        _ => None,
+      }
+    })
+    .unwrap_or_else(|| default_system_color(color, theme))
+}
+
+/// Black or white, whichever is more readable on `background`.
+/// See <https://www.w3.org/TR/WCAG21/#dfn-relative-luminance>
+fn contrasting(background: Color) -> Color {
+  let linear = |c: f32| if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) };
+  let luminance = 0.2126 * linear(background.r) + 0.7152 * linear(background.g) + 0.0722 * linear(background.b);
+  // Luminance for which the contrast ratios with black and white are equal.
+  if luminance > 0.179 {
+    Color::from_rgb8(0, 0, 0)
+  } else {
+    Color::from_rgb8(255, 255, 255)
+  }
+}
+
+/// Deprecated system colors are aliases of the modern ones.
+/// See <https://drafts.csswg.org/css-color/#deprecated-system-colors>
+fn modern_system_color(color: SystemColor) -> SystemColor {
+  use SystemColor::{
+    ActiveBorder, ActiveCaption, AppWorkspace, Background, ButtonBorder, ButtonFace, ButtonHighlight, ButtonShadow, Canvas, CanvasText, CaptionText, GrayText,
+    InactiveBorder, InactiveCaption, InactiveCaptionText, InfoBackground, InfoText, Menu, MenuText, Scrollbar, ThreeDDarkShadow, ThreeDFace, ThreeDHighlight,
+    ThreeDLightShadow, ThreeDShadow, Window, WindowFrame, WindowText,
+  };
+  match color {
+    ActiveCaption | AppWorkspace | Background | InactiveCaption | InfoBackground | Menu | Scrollbar | Window => Canvas,
+    CaptionText | InfoText | MenuText | WindowText => CanvasText,
+    ActiveBorder | InactiveBorder | ThreeDDarkShadow | ThreeDLightShadow | ThreeDShadow | WindowFrame => ButtonBorder,
+    ButtonHighlight | ButtonShadow | ThreeDFace | ThreeDHighlight => ButtonFace,
+    InactiveCaptionText => GrayText,
+    color => color,
+  }
+}
+
+fn default_system_color(color: SystemColor, theme: SystemTheme) -> Color {
+  use SystemColor::{
+    AccentColor, AccentColorText, ActiveText, ButtonBorder, ButtonFace, ButtonText, CanvasText, Field, FieldText, GrayText, Highlight, HighlightText, LinkText,
+    Mark, MarkText, SelectedItem, SelectedItemText, VisitedText,
+  };
+  let (light, dark) = match color {
+    AccentColor | SelectedItem => (0x0075ff, 0x3b8eea),
+    AccentColorText | SelectedItemText => (0xffffff, 0xffffff),
+    ButtonBorder => (0x767676, 0x858585),
+    ButtonFace => (0xefefef, 0x3b3b3b),
+    Field => (0xffffff, 0x3b3b3b),
+    GrayText => (0x808080, 0x8b8b8b),
+    Highlight => (0xb5d5ff, 0x2f5a8a),
+    LinkText => (0x0000ee, 0x9e9eff),
+    VisitedText => (0x551a8b, 0xd0adf0),
+    ActiveText => (0xee0000, 0xff6666),
+    Mark => (0xffff00, 0xffff00),
+    MarkText => (0x000000, 0x000000),
+    ButtonText | CanvasText | FieldText | HighlightText => (0x000000, 0xffffff),
+    // Canvas, and the deprecated colors.
+    _ => (0xffffff, 0x1e1e1e),
+  };
+  let rgb = match theme {
+    SystemTheme::Light => light,
+    SystemTheme::Dark => dark,
+  };
+  Color::from_rgb8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
@@ -4,12 +4,14 @@ use std::ptr;
 
 use anyhow::{bail, Result};
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use lightningcss::values::color::SystemColor;
 use log::error;
 use objc2::foundation::{is_main_thread, MainThreadMarker, NSArray, NSObject, NSString};
 use objc2::rc::{Id, Shared};
 use objc2::runtime::Object;
 use objc2::{class, declare_class, extern_class, extern_methods, msg_send, msg_send_id, sel, ClassType};
 
+use crate::properties::Color;
 use crate::themes::{Event, SystemTheme};
 
 // FIXME: Event sender is unsafe #2
@@ -36,6 +38,65 @@ pub(crate) fn get_system_theme() -> SystemTheme {
   }
 }
 
+/// System colors of AppKit, resolved for the current appearance.
+pub(crate) fn system_color(color: SystemColor) -> Option<Color> {
+  let get_color = move || {
+    use SystemColor::{
+      AccentColor, AccentColorText, ButtonBorder, ButtonFace, ButtonText, Canvas, CanvasText, Field, FieldText, GrayText, Highlight, HighlightText, LinkText,
+      Mark, SelectedItem, SelectedItemText,
+    };
+    let app = NSApp();
+    let appearance = app.effectiveAppearance();
+    let resolve = || unsafe {
+      let ns_color: Option<Id<Object, Shared>> = match color {
+        AccentColor | SelectedItem => msg_send_id![class!(NSColor), controlAccentColor],
+        AccentColorText | SelectedItemText => msg_send_id![class!(NSColor), alternateSelectedControlTextColor],
+        Canvas | Field => msg_send_id![class!(NSColor), textBackgroundColor],
+        CanvasText | FieldText => msg_send_id![class!(NSColor), textColor],
+        ButtonFace => msg_send_id![class!(NSColor), controlColor],
+        ButtonText => msg_send_id![class!(NSColor), controlTextColor],
+        ButtonBorder => msg_send_id![class!(NSColor), separatorColor],
+        GrayText => msg_send_id![class!(NSColor), disabledControlTextColor],
+        Highlight => msg_send_id![class!(NSColor), selectedTextBackgroundColor],
+        HighlightText => msg_send_id![class!(NSColor), selectedTextColor],
+        LinkText => msg_send_id![class!(NSColor), linkColor],
+        Mark => msg_send_id![class!(NSColor), findHighlightColor],
+        _ => None,
+      };
+      let srgb: Id<Object, Shared> = msg_send_id![class!(NSColorSpace), sRGBColorSpace];
+      let rgb: Option<Id<Object, Shared>> = msg_send_id![&ns_color?, colorUsingColorSpace: &*srgb];
+      let rgb = rgb?;
+      let (r, g, b, a): (f64, f64, f64, f64) = (
+        msg_send![&rgb, redComponent],
+        msg_send![&rgb, greenComponent],
+        msg_send![&rgb, blueComponent],
+        msg_send![&rgb, alphaComponent],
+      );
+      Some(Color {
+        r: r as f32,
+        g: g as f32,
+        b: b as f32,
+        a: a as f32,
+      })
+    };
+    // Dynamic colors are resolved against the current appearance of the
+    // thread, which is restored for the application.
+    unsafe {
+      let previous: Option<Id<Object, Shared>> = msg_send_id![class!(NSAppearance), currentAppearance];
+      let _: () = msg_send![class!(NSAppearance), setCurrentAppearance: &*appearance];
+      let color = resolve();
+      let _: () = msg_send![class!(NSAppearance), setCurrentAppearance: previous.as_deref()];
+      color
+    }
+  };
+
+  if is_main_thread() {
+    get_color()
+  } else {
+    dispatch::Queue::main().exec_sync(get_color)
+  }
+}
+
 extern_class!(
   #[derive(Debug, PartialEq, Eq, Hash)]
   pub(crate) struct NSResponder;
@@ -103,15 +164,18 @@ declare_class!(
       let this: Option<&mut Self> = unsafe { msg_send![self, init] };
       this.map(|this| {
         let notification_center: Id<Object, Shared> = unsafe { msg_send_id![class!(NSDistributedNotificationCenter), defaultCenter] };
-        let notification_name = NSString::from_str("AppleInterfaceThemeChangedNotification");
-        unsafe {
-          let _: () = msg_send![
-            &notification_center,
-            addObserver: &*this
-              selector: sel!(effectiveAppearanceDidChange:)
-              name: &*notification_name
-              object: ptr::null::<Object>()
-          ];
+        // Light/dark theme, and accent color changes.
+        for name in ["AppleInterfaceThemeChangedNotification", "AppleColorPreferencesChangedNotification"] {
+          let notification_name = NSString::from_str(name);
+          unsafe {
+            let _: () = msg_send![
+              &notification_center,
+              addObserver: &*this
+                selector: sel!(effectiveAppearanceDidChange:)
+                name: &*notification_name
+                object: ptr::null::<Object>()
+            ];
+          }
         }
         this
       })
@@ -133,7 +197,7 @@ pub(crate) struct Watcher {
   pub(crate) recv: Receiver<Event>,
 }
 
-/// Sends `Event::Changed` when system-wide theme changed.
+/// Sends `Event::Changed` when system-wide theme or accent color changed.
 ///
 /// # Errors
 ///
@@ -3,7 +3,9 @@ use std::cell::{Cell, RefCell};
 
 use anyhow::{bail, Result};
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use lightningcss::values::color::SystemColor;
 
+use crate::properties::Color;
 use crate::themes::{Event, SystemTheme};
 
 thread_local! {
@@ -25,6 +27,11 @@ pub(crate) fn get_system_theme() -> SystemTheme {
   THEME.with(|t| t.get())
 }
 
+/// The defaults of the theme are used.
+pub(crate) fn system_color(_: SystemColor) -> Option<Color> {
+  None
+}
+
 pub(crate) struct Watcher {
   pub(crate) recv: Receiver<Event>,
 }
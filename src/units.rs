@@ -0,0 +1,125 @@
+use lightningcss::values::calc::{Calc, MathFunction};
+use lightningcss::values::length::{Length as CssLength, LengthPercentage, LengthValue};
+use lightningcss::values::percentage::DimensionPercentage;
+
+use crate::properties::{CalcLength, Context, Length};
+
+/// Resolves relative lengths and `calc()` expressions.
+pub(crate) struct Lengths<'a> {
+  pub(crate) context: &'a Context,
+  /// Font size `em` units are relative to.
+  pub(crate) font_size: f32,
+}
+
+impl<'a> Lengths<'a> {
+  /// Length in pixels. `None` when the value depends on the unknown
+  /// viewport, or its unit can't be resolved.
+  fn px(&self, v: &LengthValue) -> Option<f32> {
+    use LengthValue::{Ch, Em, Ex, Rem, Vh, Vmax, Vmin, Vw};
+    let viewport = self.context.viewport;
+    match v {
+      Em(v) => Some(v * self.font_size),
+      Rem(v) => Some(v * self.context.root_font_size),
+      // Without font metrics, `ex` and `ch` are approximated as half an `em`.
+      Ex(v) | Ch(v) => Some(v * self.font_size / 2.0),
+      Vw(v) => viewport.map(|(w, _)| v * w / 100.0),
+      Vh(v) => viewport.map(|(_, h)| v * h / 100.0),
+      Vmin(v) => viewport.map(|(w, h)| v * w.min(h) / 100.0),
+      Vmax(v) => viewport.map(|(w, h)| v * w.max(h) / 100.0),
+      // px, in, cm, mm, q, pt, pc
+      v => v.to_px(),
+    }
+  }
+
+  /// Known parts of `v` in pixels, and its unknown viewport-relative parts.
+  /// `None` for units that can't be resolved, like `lh`, `cap`, `dvh` or
+  /// container units.
+  fn sum(&self, v: &LengthValue) -> Option<CalcLength> {
+    use LengthValue::{Vh, Vmax, Vmin, Vw};
+    let unknown = CalcLength::default();
+    match (self.px(v), v) {
+      (Some(px), _) => Some(CalcLength { px, ..unknown }),
+      (None, Vw(v)) => Some(CalcLength { vw: *v, ..unknown }),
+      (None, Vh(v)) => Some(CalcLength { vh: *v, ..unknown }),
+      (None, Vmin(v)) => Some(CalcLength { vmin: *v, ..unknown }),
+      (None, Vmax(v)) => Some(CalcLength { vmax: *v, ..unknown }),
+      (None, _) => None,
+    }
+  }
+
+  /// Length in pixels.
+  pub(crate) fn length(&self, l: &CssLength) -> Option<f32> {
+    match l {
+      CssLength::Value(v) => self.px(v),
+      CssLength::Calc(c) => calc(c, &|l| self.length(l)),
+    }
+  }
+
+  /// Viewport-relative lengths are kept as such if the viewport is unknown.
+  /// `None` for units that can't be resolved.
+  pub(crate) fn typed_length(&self, l: &CssLength) -> Option<Length> {
+    self.length_sum(l).map(CalcLength::into_length)
+  }
+
+  fn length_sum(&self, l: &CssLength) -> Option<CalcLength> {
+    match l {
+      CssLength::Value(v) => self.sum(v),
+      CssLength::Calc(c) => linear_calc(c, &|l| self.length_sum(l)),
+    }
+  }
+
+  /// Percentages are resolved against `basis`, or kept as percentages.
+  /// `None` for units that can't be resolved, and for `min()`, `max()` and
+  /// `clamp()` of unknown lengths.
+  pub(crate) fn length_percentage(&self, l: &LengthPercentage, basis: Option<f32>) -> Option<Length> {
+    self.length_percentage_sum(l, basis).map(CalcLength::into_length)
+  }
+
+  fn length_percentage_sum(&self, l: &LengthPercentage, basis: Option<f32>) -> Option<CalcLength> {
+    match (l, basis) {
+      (DimensionPercentage::Dimension(v), _) => self.sum(v),
+      (DimensionPercentage::Percentage(p), Some(b)) => Some(CalcLength::px(p.0 * b)),
+      (DimensionPercentage::Percentage(p), None) => {
+        Some(CalcLength {
+          percent: p.0 * 100.0,
+          ..CalcLength::default()
+        })
+      },
+      (DimensionPercentage::Calc(c), _) => linear_calc(c, &|l| self.length_percentage_sum(l, basis)),
+    }
+  }
+}
+
+/// Sums and products of unknown lengths are kept as a sum. `min()`, `max()`
+/// and `clamp()` are only resolved when all their arguments are known.
+fn linear_calc<V>(c: &Calc<V>, value: &impl Fn(&V) -> Option<CalcLength>) -> Option<CalcLength> {
+  match c {
+    Calc::Value(v) => value(v),
+    Calc::Number(n) => Some(CalcLength::px(*n)),
+    Calc::Sum(a, b) => Some(linear_calc(a, value)? + linear_calc(b, value)?),
+    Calc::Product(n, c) => Some(linear_calc(c, value)? * *n),
+    Calc::Function(f) => {
+      match &**f {
+        MathFunction::Calc(c) => linear_calc(c, value),
+        _ => calc(c, &|v| value(v)?.to_known_px()).map(CalcLength::px),
+      }
+    },
+  }
+}
+
+fn calc<V>(c: &Calc<V>, value: &impl Fn(&V) -> Option<f32>) -> Option<f32> {
+  match c {
+    Calc::Value(v) => value(v),
+    Calc::Number(n) => Some(*n),
+    Calc::Sum(a, b) => Some(calc(a, value)? + calc(b, value)?),
+    Calc::Product(n, c) => Some(n * calc(c, value)?),
+    Calc::Function(f) => {
+      match &**f {
+        MathFunction::Calc(c) => calc(c, value),
+        MathFunction::Min(values) => values.iter().map(|c| calc(c, value)).reduce(|a, b| Some(a?.min(b?)))?,
+        MathFunction::Max(values) => values.iter().map(|c| calc(c, value)).reduce(|a, b| Some(a?.max(b?)))?,
+        MathFunction::Clamp(min, v, max) => Some(calc(v, value)?.min(calc(max, value)?).max(calc(min, value)?)),
+      }
+    },
+  }
+}
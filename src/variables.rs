@@ -0,0 +1,272 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+use lightningcss::cssparser::{Parser, ParserInput};
+use lightningcss::printer::Printer;
+use lightningcss::properties::custom::{CustomProperty, Function, TokenList, TokenOrValue};
+use lightningcss::properties::{Property, PropertyId};
+use lightningcss::rules::property::PropertyRule;
+use lightningcss::stylesheet::{ParserOptions, PrinterOptions};
+use lightningcss::traits::{IntoOwned, ToCss};
+use lightningcss::values::color::SystemColor;
+use lightningcss::values::syntax::{ParsedComponent, SyntaxString};
+
+use crate::properties::WideKeyword;
+use crate::themes::SystemColors;
+
+/// Custom property registered with `@property`.
+/// See <https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule>
+pub(crate) struct Registration {
+  syntax: SyntaxString,
+  inherits: bool,
+  initial: Option<TokenList<'static>>,
+}
+
+impl Registration {
+  pub(crate) fn new(rule: &PropertyRule<'_>) -> Registration {
+    let initial = rule.initial_value.as_ref().and_then(|value| {
+      let source = value.to_css_string(PrinterOptions::default()).ok()?;
+      let id = PropertyId::Custom(rule.name.0.clone());
+      match Property::parse_string(id, &source, ParserOptions::default()) {
+        Ok(Property::Custom(CustomProperty { value, .. })) => Some(value.into_owned()),
+        _ => None,
+      }
+    });
+    Registration {
+      syntax: rule.syntax.clone(),
+      inherits: rule.inherits,
+      initial,
+    }
+  }
+
+  /// Parse the computed value `tokens` with the registered syntax, `None` if
+  /// it doesn't match.
+  fn parse(&self, tokens: &TokenList<'_>) -> Option<ParsedComponent<'static>> {
+    if matches!(self.syntax, SyntaxString::Universal) {
+      return Some(ParsedComponent::TokenList(tokens.clone().into_owned()));
+    }
+    // FIXME: Serialised to be parsed with the syntax, like `parse_tokens` #1
+    let mut source = String::new();
+    {
+      let mut printer = Printer::new(&mut source, PrinterOptions::default());
+      tokens.to_css(&mut printer, false).ok()?;
+    }
+    let mut input = ParserInput::new(&source);
+    let mut parser = Parser::new(&mut input);
+    // The whole value has to match, not only its beginning.
+    let parsed = parser.parse_entirely(|parser| self.syntax.parse_value(parser)).ok()?;
+    Some(parsed.into_owned())
+  }
+}
+
+#[derive(Clone, Copy)]
+enum Declared<'a, 'i> {
+  Value(&'a TokenList<'i>),
+  Keyword(WideKeyword),
+}
+
+/// Computes custom properties of an element and substitutes `var()`
+/// references at the token level.
+/// See <https://drafts.csswg.org/css-variables/#substitute-a-var>
+pub(crate) struct Resolver<'a, 'i> {
+  /// Computed values inherited from the parent.
+  inherited: &'a HashMap<String, TokenList<'static>>,
+  /// Custom properties registered with `@property`.
+  registered: &'a HashMap<String, Registration>,
+  /// Custom properties set by the system, used when no element sets them.
+  system: &'a HashMap<String, TokenList<'static>>,
+  /// Values declared on the element.
+  declared: HashMap<String, Declared<'a, 'i>>,
+  /// Computed values, `None` if invalid at computed-value time.
+  computed: HashMap<String, Option<TokenList<'i>>>,
+  /// Computed values of the registered custom properties, parsed with their
+  /// syntax.
+  typed: HashMap<String, ParsedComponent<'static>>,
+  /// Custom properties being computed, to detect cycles.
+  resolving: Vec<String>,
+  /// Custom properties part of a dependency cycle.
+  cyclic: HashSet<String>,
+}
+
+impl<'a, 'i> Resolver<'a, 'i> {
+  pub(crate) fn new(
+    inherited: &'a HashMap<String, TokenList<'static>>,
+    registered: &'a HashMap<String, Registration>,
+    system: &'a HashMap<String, TokenList<'static>>,
+  ) -> Resolver<'a, 'i> {
+    Resolver {
+      inherited,
+      registered,
+      system,
+      declared: HashMap::new(),
+      computed: HashMap::new(),
+      typed: HashMap::new(),
+      resolving: vec![],
+      cyclic: HashSet::new(),
+    }
+  }
+
+  /// Declare `name: value`. Later declarations win.
+  pub(crate) fn declare(&mut self, name: &str, value: &'a TokenList<'i>) {
+    let declared = match WideKeyword::from_tokens(value) {
+      Some(keyword) => Declared::Keyword(keyword),
+      None => Declared::Value(value),
+    };
+    self.declared.insert(name.to_owned(), declared);
+  }
+
+  /// Computed values of all the custom properties of the element, and the
+  /// values of the registered ones parsed with their syntax.
+  pub(crate) fn computed(&mut self) -> (HashMap<String, TokenList<'static>>, HashMap<String, ParsedComponent<'static>>) {
+    let names: HashSet<String> = self
+      .inherited
+      .keys()
+      .chain(self.registered.keys())
+      .chain(self.declared.keys())
+      .cloned()
+      .collect();
+    let variables: HashMap<_, _> = names
+      .into_iter()
+      .filter_map(|name| self.variable(&name).map(|value| (name, value.into_owned())))
+      .collect();
+    // Inherited and initial values were not parsed on this element.
+    let registered = self.registered;
+    let typed = variables
+      .iter()
+      .filter_map(|(name, value)| {
+        let registration = registered.get(name)?;
+        let typed = self.typed.remove(name).or_else(|| registration.parse(value))?;
+        Some((name.clone(), typed))
+      })
+      .collect();
+    (variables, typed)
+  }
+
+  /// Replace all the `var()` references of `tokens`, including the ones
+  /// nested in functions and fallbacks. Returns `None` if the value is
+  /// invalid at computed-value time.
+  pub(crate) fn substitute(&mut self, tokens: &TokenList<'i>) -> Option<TokenList<'i>> {
+    let mut result = Vec::with_capacity(tokens.0.len());
+    for token in &tokens.0 {
+      match token {
+        TokenOrValue::Var(var) => {
+          let value = match self.variable(var.name.ident.0.as_ref()) {
+            Some(value) => value,
+            None => self.substitute(var.fallback.as_ref()?)?,
+          };
+          result.extend(value.0);
+        },
+        TokenOrValue::Function(f) => {
+          let arguments = self.substitute(&f.arguments)?;
+          result.push(TokenOrValue::Function(Function {
+            name: f.name.clone(),
+            arguments,
+          }));
+        },
+        t => result.push(t.clone()),
+      }
+    }
+    Some(TokenList(result))
+  }
+
+  fn variable(&mut self, name: &str) -> Option<TokenList<'i>> {
+    if let Some(value) = self.computed.get(name) {
+      return value.clone();
+    }
+    let value = match self.declared.get(name).copied() {
+      None | Some(Declared::Keyword(WideKeyword::Unset | WideKeyword::Revert)) => self.unset(name),
+      Some(Declared::Keyword(WideKeyword::Inherit)) => self.inherited.get(name).or_else(|| self.system.get(name)).cloned(),
+      Some(Declared::Keyword(WideKeyword::Initial)) => self.initial(name),
+      Some(Declared::Value(declared)) => {
+        if let Some(start) = self.resolving.iter().position(|n| n == name) {
+          // All the custom properties of a cycle are invalid at computed-value
+          // time, even if they have a fallback.
+          self.cyclic.extend(self.resolving[start..].iter().cloned());
+          return None;
+        }
+        self.resolving.push(name.to_owned());
+        let value = self.substitute(declared);
+        self.resolving.pop();
+        let value = value.filter(|_| !self.cyclic.contains(name));
+        let registered = self.registered;
+        match registered.get(name) {
+          None => value,
+          Some(registration) => {
+            match value.as_ref().and_then(|value| registration.parse(value)) {
+              Some(typed) => {
+                self.typed.insert(name.to_owned(), typed);
+                value
+              },
+              // Invalid at computed-value time: registered properties are
+              // unset, the others get the guaranteed-invalid value.
+              None => self.unset(name),
+            }
+          },
+        }
+      },
+    };
+    self.computed.insert(name.to_owned(), value.clone());
+    value
+  }
+
+  fn initial(&self, name: &str) -> Option<TokenList<'i>> {
+    self.registered.get(name).and_then(|r| r.initial.clone())
+  }
+
+  /// Value of a custom property that is not declared. Custom properties are
+  /// inherited, unless registered otherwise.
+  fn unset(&self, name: &str) -> Option<TokenList<'i>> {
+    match self.registered.get(name) {
+      Some(r) if !r.inherits => r.initial.clone(),
+      Some(r) => self.inherited.get(name).or(r.initial.as_ref()).cloned(),
+      None => self.inherited.get(name).or_else(|| self.system.get(name)).cloned(),
+    }
+  }
+}
+
+/// Whether `tokens` contains a `var()` reference.
+pub(crate) fn has_references(tokens: &TokenList<'_>) -> bool {
+  tokens.0.iter().any(|t| {
+    match t {
+      TokenOrValue::Var(_) => true,
+      TokenOrValue::Function(f) => has_references(&f.arguments),
+      _ => false,
+    }
+  })
+}
+
+/// Parse `tokens`, the value of a property after `var()` substitution, as
+/// a value of `property_id`. Values that don't match the grammar of the
+/// property are invalid at computed-value time.
+// FIXME: Substituted values are serialised and parsed again #1. cssparser
+// only reads source text, it has no `ParserInput` over a token list, so
+// lightningcss can't parse a property from tokens.
+pub(crate) fn parse_tokens(property_id: &PropertyId<'_>, tokens: &TokenList<'_>) -> Result<Property<'static>> {
+  let mut source = String::new();
+  let mut printer = Printer::new(&mut source, PrinterOptions::default());
+  tokens.to_css(&mut printer, false)?;
+  match Property::parse_string(property_id.clone(), &source, ParserOptions::default()) {
+    // Values that fail to parse are kept unparsed.
+    Ok(Property::Unparsed(_)) | Err(_) => bail!("Invalid value `{source}` for property {}", property_id.name()),
+    Ok(prop) => Ok(prop.into_owned()),
+  }
+}
+
+/// Custom properties set by the system, like the accent color chosen by the
+/// user (`--system-accent`). They behave as if they were inherited from
+/// above the root element.
+pub(crate) fn system_variables(colors: &SystemColors) -> HashMap<String, TokenList<'static>> {
+  let accent = colors.get(SystemColor::AccentColor);
+  let accent_text = colors.get(SystemColor::AccentColorText);
+  [("--system-accent", accent), ("--system-accent-text", accent_text)]
+    .into_iter()
+    .filter_map(|(name, c)| {
+      // `color(srgb)` keeps the colors outside of the sRGB gamut.
+      let source = format!("color(srgb {} {} {} / {})", c.r, c.g, c.b, c.a);
+      match Property::parse_string(PropertyId::Custom(name.into()), &source, ParserOptions::default()) {
+        Ok(Property::Custom(CustomProperty { value, .. })) => Some((name.to_owned(), value.into_owned())),
+        _ => None,
+      }
+    })
+    .collect()
+}